@@ -37,6 +37,16 @@ impl ByondPath {
     pub fn is_rooted(&self) -> bool {
         self.rooted
     }
+
+    /// Returns a new path with the given segment appended.
+    pub fn child<A: AsRef<str>>(&self, name: A) -> ByondPath {
+        let mut segments = self.segments.clone();
+        segments.push(name.as_ref().to_owned());
+        ByondPath {
+            segments,
+            rooted: self.rooted,
+        }
+    }
 }
 
 impl fmt::Display for ByondPath {
@@ -77,11 +87,55 @@ pub struct CompilerState {
     pub global_vars: HashMap<String, GlobalVar>,
 }
 
+impl CompilerState {
+    /// Looks up a proc on a type, walking up the inheritance chain if the type doesn't define it.
+    pub fn get_type_proc(&self, path: &ByondPath, name: &str) -> Option<&Proc> {
+        let mut current = self.types.get(path);
+        while let Some(compiler_type) = current {
+            if let Some(found) = compiler_type.procs.get(name) {
+                return Some(found);
+            }
+
+            current = compiler_type
+                .parent
+                .as_ref()
+                .and_then(|parent| self.types.get(parent));
+        }
+
+        None
+    }
+
+    /// Looks up the declaration of a var on a type, walking up the inheritance chain.
+    /// Returns the path of the declaring type along with the var.
+    pub fn get_type_var(&self, path: &ByondPath, name: &str) -> Option<(&ByondPath, &TypeVar)> {
+        let mut current = self.types.get(path);
+        while let Some(compiler_type) = current {
+            if let Some(found) = compiler_type.vars.get(name) {
+                if found.is_declaration {
+                    return Some((&compiler_type.path, found));
+                }
+            }
+
+            current = compiler_type
+                .parent
+                .as_ref()
+                .and_then(|parent| self.types.get(parent));
+        }
+
+        None
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CompilerType {
     pub path: ByondPath,
+    /// The type this inherits from, if not the root.
+    /// Not necessarily the same as the path's parent, e.g. /obj inherits /atom/movable.
+    pub parent: Option<ByondPath>,
     pub children: Vec<String>,
     pub procs: HashMap<String, Proc>,
+    /// Vars declared on this type, or inherited vars that this type overrides the value of.
+    pub vars: HashMap<String, TypeVar>,
     pub special_class: Option<SpecialClass>,
 }
 
@@ -89,8 +143,10 @@ impl CompilerType {
     pub fn new(path: &ByondPath) -> CompilerType {
         CompilerType {
             path: path.clone(),
+            parent: None,
             children: vec![],
             procs: HashMap::new(),
+            vars: HashMap::new(),
             special_class: None,
         }
     }
@@ -105,9 +161,13 @@ pub enum SpecialClass {
 pub struct Proc {
     pub name: String,
     pub parameters: Vec<ProcParameter>,
+    /// Takes any number of arguments, passed as a single `object[]`.
     pub var_arg: bool,
     pub source: ProcSource,
     pub is_static: bool,
+    /// The type this proc is defined on, `None` for global procs.
+    pub owner: Option<ByondPath>,
+    pub settings: ProcSettings,
 }
 
 impl Proc {
@@ -118,6 +178,26 @@ impl Proc {
             var_arg: false,
             source,
             is_static: false,
+            owner: None,
+            settings: ProcSettings::default(),
+        }
+    }
+}
+
+/// The options set with `set` statements in a proc.
+#[derive(Clone, Debug)]
+pub struct ProcSettings {
+    /// Whether long running loops in the proc should sleep to let other procs run.
+    pub background: bool,
+    /// If false, callers continue as soon as the proc sleeps instead of waiting for it to return.
+    pub waitfor: bool,
+}
+
+impl Default for ProcSettings {
+    fn default() -> ProcSettings {
+        ProcSettings {
+            background: false,
+            waitfor: true,
         }
     }
 }
@@ -126,6 +206,8 @@ impl Proc {
 pub struct ProcParameter {
     pub name: String,
     pub var_type: VariableType,
+    /// Used when the argument is null or not passed at all.
+    pub default: Option<Expression>,
 }
 
 impl ProcParameter {
@@ -133,6 +215,7 @@ impl ProcParameter {
         ProcParameter {
             name: name.to_owned(),
             var_type,
+            default: None,
         }
     }
 }
@@ -147,8 +230,106 @@ pub enum ProcSource {
 pub enum StdProc {
     Abs,
     WorldOutput,
+    WorldReboot,
+    WorldExport,
+    WorldImport,
+    WorldShellEo,
     Sin,
     Cos,
+    Tan,
+    ArcSin,
+    ArcCos,
+    ArcTan,
+    Sqrt,
+    Log,
+    Round,
+    Floor,
+    Ceil,
+    Trunc,
+    Fract,
+    Clamp,
+    Sign,
+    Lerp,
+    Rand,
+    Prob,
+    Pick,
+    Roll,
+    RandSeed,
+    JsonEncode,
+    JsonDecode,
+    Md5,
+    Sha1,
+    UrlEncode,
+    UrlDecode,
+    List2Params,
+    Params2List,
+    CKey,
+    CKeyEx,
+    HtmlEncode,
+    HtmlDecode,
+    Rgb,
+    Rgb2Num,
+    File,
+    File2Text,
+    Text2File,
+    FExists,
+    FDel,
+    FCopy,
+    FList,
+    Regex,
+    // Procs of /regex.
+    RegexNew,
+    RegexFind,
+    RegexReplace,
+    // Procs of /savefile.
+    SavefileNew,
+    SavefileExportText,
+    SavefileImportText,
+    SavefileFlush,
+    Text2Path,
+    IsType,
+    IsPath,
+    IsNum,
+    IsText,
+    IsNull,
+    IsList,
+    IsLoc,
+    IsTurf,
+    IsMob,
+    IsObj,
+    IsArea,
+    IsIcon,
+    IsFile,
+    TypesOf,
+    SubtypesOf,
+    Sleep,
+    Time2Text,
+    Alert,
+    Shell,
+    Min,
+    Max,
+    // Text procs. `chars` is for the `_char` versions, which count characters instead of bytes.
+    Length { chars: bool },
+    CopyText { chars: bool },
+    FindText { chars: bool },
+    FindTextEx { chars: bool },
+    FindLastText { chars: bool },
+    FindLastTextEx { chars: bool },
+    ReplaceText { chars: bool },
+    ReplaceTextEx { chars: bool },
+    SplitText { chars: bool },
+    JoinText,
+    UpperText,
+    LowerText,
+    Text2Ascii { chars: bool },
+    Ascii2Text,
+    TrimText,
+    SpanText { chars: bool },
+    NonSpanText { chars: bool },
+    CmpText,
+    SortText,
+    Num2Text,
+    Text2Num,
     Unimplemented(String),
 }
 
@@ -158,6 +339,17 @@ pub enum VariableType {
     Object(ByondPath),
 }
 
+impl VariableType {
+    /// Gets the variable type from a declaration's type path, like the `/mob` in `var/mob/M`.
+    pub fn from_type_path<P: AsRef<str>>(type_path: &[P]) -> VariableType {
+        if type_path.len() == 0 {
+            VariableType::Unspecified
+        } else {
+            VariableType::Object(ByondPath::new(type_path, true))
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GlobalVar {
     pub name: String,
@@ -180,6 +372,41 @@ impl GlobalVar {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct TypeVar {
+    pub name: String,
+    pub var_type: VariableType,
+    /// Whether the type declares this var, instead of only overriding its value.
+    /// The fields below are only meaningful for declarations.
+    pub is_declaration: bool,
+    pub initializer: Option<VariableInitializer>,
+    pub mutability: VariableMutability,
+    pub is_static: bool,
+    pub is_tmp: bool,
+}
+
+impl TypeVar {
+    pub fn new<A>(name: A, var_type: &VariableType, is_declaration: bool) -> TypeVar
+    where
+        A: AsRef<str>,
+    {
+        TypeVar {
+            name: name.as_ref().to_owned(),
+            var_type: var_type.clone(),
+            is_declaration,
+            initializer: None,
+            mutability: VariableMutability::Normal,
+            is_static: false,
+            is_tmp: false,
+        }
+    }
+
+    /// Whether the var gets written to savefiles, see `issaved()`.
+    pub fn is_saved(&self) -> bool {
+        !self.is_static && !self.is_tmp && self.mutability != VariableMutability::Constant
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum VariableInitializer {
     Constant(Constant),
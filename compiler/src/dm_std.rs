@@ -1,8 +1,10 @@
 use crate::compiler_state::*;
-use crate::compiler_warning;
+use crate::CompilerError;
 use crate::dmstate::DMState;
 use crate::il::*;
-use crate::proc_transpiler::evaluate_initializer;
+use crate::proc_transpiler::{
+    byond_path_to_class, evaluate_initializer, load_type_path, write_stringify,
+};
 use dreammaker::constants::Constant;
 
 /*
@@ -35,53 +37,117 @@ pub(crate) fn create_global_cctor(
         "instance void byond_root/world::'.ctor' ()".to_owned(),
     ));
     code.instruction(Instruction::stsfld("object byond_root::world".to_owned()));
+    code.instruction(Instruction::ldsfld("object byond_root::world".to_owned()));
+    code.instruction(Instruction::call("void [DM]DM.DmWorld::Init(object)".to_owned()));
+
+    // Register every type path with the runtime, so text2path() and friends can find them.
+    let mut type_paths = state.types.keys().collect::<Vec<&ByondPath>>();
+    type_paths.sort_unstable_by_key(|path| path.to_string());
+    for path in &type_paths {
+        load_type_path(path, &mut code);
+        code.instruction(Instruction::pop);
+    }
 
     for (name, var) in &state.global_vars {
         let field_name = format!("object byond_root::{}", name);
-        match &var.initializer {
-            Some(VariableInitializer::Constant(constant)) => {
-                match constant {
-                    // Null is already loaded in those vars so yay.
-                    Constant::Null(_) => {}
-                    Constant::Int(val) => {
-                        code.instruction(Instruction::ldcr4(*val as f32));
-                        code.instruction(Instruction::_box("[mscorlib]System.Single".into()));
-                        code.instruction(Instruction::stsfld(field_name));
-                    }
-                    Constant::Float(val) => {
-                        code.instruction(Instruction::ldcr4(val.raw()));
-                        code.instruction(Instruction::_box("[mscorlib]System.Single".into()));
-                        code.instruction(Instruction::stsfld(field_name));
-                    }
-                    Constant::String(string) => {
-                        code.instruction(Instruction::ldstr(string.clone()));
-                        code.instruction(Instruction::stsfld(field_name));
-                    }
-                    _ => compiler_warning(format!(
-                        "Unable to write constant initializer for global var {}",
-                        name
-                    )),
+        match load_initializer(
+            &var.initializer,
+            class,
+            &format!("{}_init", &name),
+            dm_state,
+            state,
+            &mut code,
+        ) {
+            // Null is already loaded in those vars so yay.
+            Ok(false) => {}
+            Ok(true) => {
+                code.instruction(Instruction::stsfld(field_name));
+            }
+            Err(error) => {
+                println!("ERROR in initializer for {}: {:?}", name, error);
+            }
+        }
+    }
+
+    // Static vars on types are basically globals.
+    for path in &type_paths {
+        let compiler_type = &state.types[*path];
+        let mut var_names = compiler_type.vars.keys().collect::<Vec<&String>>();
+        var_names.sort_unstable();
+
+        for var_name in var_names {
+            let type_var = &compiler_type.vars[var_name];
+            if !type_var.is_declaration || !type_var.is_static {
+                continue;
+            }
+
+            let field_name = format!(
+                "object {}::'{}'",
+                byond_path_to_class(path),
+                var_name
+            );
+            match load_initializer(
+                &type_var.initializer,
+                class,
+                &format!("{}_init", var_name),
+                dm_state,
+                state,
+                &mut code,
+            ) {
+                Ok(false) => {}
+                Ok(true) => {
+                    code.instruction(Instruction::stsfld(field_name));
                 }
+                Err(error) => println!(
+                    "ERROR in initializer for {}/{}: {}",
+                    path, var_name, error
+                ),
             }
-            Some(VariableInitializer::Expression(expression)) => {
-                match evaluate_initializer(
-                    &expression,
-                    class,
-                    &format!("{}_init", &name),
-                    dm_state,
-                    state,
-                    &mut code,
-                ) {
-                    Ok(_var_type) => {
-                        code.instruction(Instruction::stsfld(field_name));
-                    }
-                    Err(error) => {
-                        println!("ERROR in initializer for {}: {:?}", name, error);
-                    }
+        }
+    }
+
+    // Tell the runtime about the default values of vars, for initial() and such.
+    for path in &type_paths {
+        let compiler_type = &state.types[*path];
+        let mut var_names = compiler_type.vars.keys().collect::<Vec<&String>>();
+        var_names.sort_unstable();
+
+        for var_name in var_names {
+            let type_var = &compiler_type.vars[var_name];
+            let is_saved = match state.get_type_var(path, var_name) {
+                Some((_, declaration)) => declaration.is_saved(),
+                None => continue,
+            };
+
+            load_type_path(path, &mut code);
+            code.instruction(Instruction::ldstr(var_name.clone()));
+            match load_initializer(
+                &type_var.initializer,
+                class,
+                &format!("{}_initial", var_name),
+                dm_state,
+                state,
+                &mut code,
+            ) {
+                Ok(true) => {}
+                Ok(false) => code.instruction(Instruction::ldnull),
+                Err(error) => {
+                    println!(
+                        "ERROR in initializer for {}/{}: {}",
+                        path, var_name, error
+                    );
+                    code.instruction(Instruction::ldnull);
                 }
             }
-            None => {}
-        };
+            code.instruction(if is_saved {
+                Instruction::ldci41
+            } else {
+                Instruction::ldci40
+            });
+            code.instruction(Instruction::callvirt(
+                "instance void [DM]DM.DmPath::AddVar(string, object, bool)".to_owned(),
+            ));
+        }
     }
 
     code.instruction(Instruction::ret);
@@ -101,13 +167,71 @@ pub(crate) fn create_global_cctor(
     cctor
 }
 
+/// Loads the value of a var initializer onto the stack.
+/// Returns false and loads nothing if the value is null.
+pub(crate) fn load_initializer(
+    initializer: &Option<VariableInitializer>,
+    class: &mut Class,
+    proc_name: &str,
+    dm_state: &DMState,
+    state: &CompilerState,
+    code: &mut InstructionBlob,
+) -> Result<bool, CompilerError> {
+    match initializer {
+        Some(VariableInitializer::Constant(constant)) => match constant {
+            Constant::Null(_) => Ok(false),
+            Constant::Int(val) => {
+                code.instruction(Instruction::ldcr4(*val as f32));
+                code.instruction(Instruction::_box("[mscorlib]System.Single".into()));
+                Ok(true)
+            }
+            Constant::Float(val) => {
+                code.instruction(Instruction::ldcr4(val.raw()));
+                code.instruction(Instruction::_box("[mscorlib]System.Single".into()));
+                Ok(true)
+            }
+            Constant::String(string) => {
+                code.instruction(Instruction::ldstr(string.clone()));
+                Ok(true)
+            }
+            Constant::Resource(path) => {
+                code.instruction(Instruction::ldstr(path.clone()));
+                code.instruction(Instruction::newobj(
+                    "instance void [DM]DM.DmFile::.ctor(string)".into(),
+                ));
+                Ok(true)
+            }
+            Constant::Prefab(pop) => {
+                let path = ByondPath::new(&pop.path, true);
+                if !state.types.contains_key(&path) {
+                    return Err(format!("Unknown type path: {}", path).into());
+                }
+                load_type_path(&path, code);
+                Ok(true)
+            }
+            _ => Err(format!("Unable to write constant initializer: {:?}", constant).into()),
+        },
+        Some(VariableInitializer::Expression(expression)) => {
+            evaluate_initializer(&expression, class, proc_name, dm_state, state, code)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
 pub fn create_stock_ctor(parent_name: &str) -> Method {
+    create_type_ctor(parent_name, InstructionBlob::default())
+}
+
+/// Creates a .ctor that runs `init` after calling the parent .ctor.
+pub fn create_type_ctor(parent_name: &str, init: InstructionBlob) -> Method {
     let mut code = InstructionBlob::default();
     code.instruction(Instruction::ldarg0);
     code.instruction(Instruction::callvirt(format!(
         "instance void {}::.ctor()",
         parent_name
     )));
+    code.absord(init);
     code.instruction(Instruction::ret);
 
     let mut ctor = Method::new(
@@ -120,37 +244,273 @@ pub fn create_stock_ctor(parent_name: &str) -> Method {
     );
     ctor.is_rt_special_name = true;
     ctor.is_special_name = true;
-    ctor.maxstack = 1;
+    ctor.maxstack = 16;
 
     ctor
 }
 
+/// Creates a static std proc that forwards its arguments straight to a method in the DM runtime.
+/// `target` is the signature of the runtime method, which must take `params.len()` objects.
+fn create_runtime_shim(name: &str, params: &[&str], target: &str) -> Method {
+    let mut method = Method::new(
+        name.to_owned(),
+        "object".to_owned(),
+        MethodAccessibility::Public,
+        MethodVirtuality::NotVirtual,
+        InstructionBlob::default(),
+        true,
+    );
+
+    for (i, param) in params.iter().enumerate() {
+        method.code.instruction(Instruction::ldarg(i as u16));
+        method.params.push(MethodParameter::new(param, "object"));
+    }
+    method
+        .code
+        .instruction(Instruction::call(target.to_owned()));
+    method.code.instruction(Instruction::ret);
+
+    method.maxstack = params.len().max(1) as u16;
+    method
+}
+
+/// Creates a static variadic std proc that forwards its arguments array to a method in the DM runtime.
+fn create_variadic_shim(name: &str, target: &str) -> Method {
+    let mut method = Method::new(
+        name.to_owned(),
+        "object".to_owned(),
+        MethodAccessibility::Public,
+        MethodVirtuality::NotVirtual,
+        InstructionBlob::default(),
+        true,
+    );
+
+    method.code.instruction(Instruction::ldarg0);
+    method.code.instruction(Instruction::call(target.to_owned()));
+    method.code.instruction(Instruction::ret);
+
+    method.params.push(MethodParameter::params_array("Args"));
+    method.maxstack = 1;
+    method
+}
+
+/// Creates a std proc on a builtin type that forwards `src` and its arguments to a method in the DM runtime.
+/// `target` is the signature of the runtime method, which must take `params.len() + 1` objects.
+fn create_instance_shim(name: &str, params: &[&str], target: &str) -> Method {
+    let mut method = Method::new(
+        name.to_owned(),
+        "object".to_owned(),
+        MethodAccessibility::Public,
+        MethodVirtuality::NotVirtual,
+        InstructionBlob::default(),
+        false,
+    );
+
+    method.code.instruction(Instruction::ldarg0);
+    for (i, param) in params.iter().enumerate() {
+        method.code.instruction(Instruction::ldarg(i as u16 + 1));
+        let mut method_param = MethodParameter::new(param, "object");
+        // Calls on untyped vars go through the dynamic binder, which needs this to leave out arguments.
+        method_param.default_value = Some("nullref".to_owned());
+        method.params.push(method_param);
+    }
+    method
+        .code
+        .instruction(Instruction::call(target.to_owned()));
+    method.code.instruction(Instruction::ret);
+
+    method.maxstack = params.len() as u16 + 1;
+    method
+}
+
+/// Parameters of the text procs that are shared between several of them.
+const FIND_PARAMS: [&str; 4] = ["Haystack", "Needle", "Start", "End"];
+const REPLACE_PARAMS: [&str; 5] = ["Haystack", "Needle", "Replacement", "Start", "End"];
+const SPLIT_PARAMS: [&str; 5] = ["Text", "Delimiter", "Start", "End", "include_delimiters"];
+const SPAN_PARAMS: [&str; 3] = ["Haystack", "Needles", "Start"];
+const JOIN_PARAMS: [&str; 4] = ["List", "Glue", "Start", "End"];
+
+/// Gets the signature of a method in the DM runtime taking `param_count` objects.
+fn runtime_target(class: &str, method: &str, param_count: usize) -> String {
+    format!(
+        "object [DM]DM.{}::{}({})",
+        class,
+        method,
+        vec!["object"; param_count].join(", ")
+    )
+}
+
+/// Creates a shim for a text proc in `DmText`.
+/// The `_char` version of `name` calls the `Char` version of `target`.
+fn create_text_shim(name: &str, target: &str, chars: bool, params: &[&str]) -> Method {
+    let (name_suffix, target_suffix) = if chars { ("_char", "Char") } else { ("", "") };
+    create_runtime_shim(
+        &format!("{}{}", name, name_suffix),
+        params,
+        &runtime_target("DmText", &format!("{}{}", target, target_suffix), params.len()),
+    )
+}
+
+/// Creates a shim for a math proc in `DmMath`.
+fn create_math_shim(name: &str, target: &str, params: &[&str]) -> Method {
+    create_runtime_shim(name, params, &runtime_target("DmMath", target, params.len()))
+}
+
+/// Creates a shim for a random number proc in `DmRandom`.
+fn create_random_shim(name: &str, target: &str, params: &[&str]) -> Method {
+    create_runtime_shim(name, params, &runtime_target("DmRandom", target, params.len()))
+}
+
+/// Creates a shim for a hashing or encoding proc in `DmEncoding`.
+fn create_encoding_shim(name: &str, target: &str, params: &[&str]) -> Method {
+    create_runtime_shim(name, params, &runtime_target("DmEncoding", target, params.len()))
+}
+
+/// Creates a shim for a file proc in `DmFile`.
+fn create_file_shim(name: &str, target: &str, params: &[&str]) -> Method {
+    create_runtime_shim(name, params, &runtime_target("DmFile", target, params.len()))
+}
+
+/// Creates a static std proc checking whether its argument is an instance of a class.
+fn create_isinst_proc(name: &str, class_name: &str) -> Method {
+    let mut method = Method::new(
+        name.to_owned(),
+        "object".to_owned(),
+        MethodAccessibility::Public,
+        MethodVirtuality::NotVirtual,
+        InstructionBlob::default(),
+        true,
+    );
+    method.code.instruction(Instruction::ldarg0);
+    method
+        .code
+        .instruction(Instruction::isinst(class_name.to_owned()));
+    method.code.instruction(Instruction::ldnull);
+    method.code.instruction(Instruction::cgtun);
+    method.code.instruction(Instruction::call(
+        "object [DM]DM.DmInternal::Bool(bool)".to_owned(),
+    ));
+    method.code.instruction(Instruction::ret);
+
+    method.params.push(MethodParameter::new("Loc", "object"));
+    method.maxstack = 2;
+    method
+}
+
 pub fn create_std_proc(std_proc: &StdProc) -> Method {
     match std_proc {
-        StdProc::Abs => {
-            let mut method = Method::new(
-                "abs".into(),
-                "object".to_owned(),
-                MethodAccessibility::Public,
-                MethodVirtuality::NotVirtual,
-                InstructionBlob::default(),
-                true,
-            );
-            method.code.instruction(Instruction::ldarg0);
-            method
-                .code
-                .instruction(Instruction::unboxany("[mscorlib]System.Single".to_owned()));
-            method.code.instruction(Instruction::call(
-                "float32 [mscorlib]System.Math::Abs(float32)".to_owned(),
-            ));
-            method
-                .code
-                .instruction(Instruction::_box("[mscorlib]System.Single".to_owned()));
-            method.code.instruction(Instruction::ret);
-
-            method.params.push(MethodParameter::new("A", "object"));
-            method.maxstack = 1;
-            method
+        StdProc::Abs => create_math_shim("abs", "Abs", &["A"]),
+        StdProc::Sin => create_math_shim("sin", "Sin", &["X"]),
+        StdProc::Cos => create_math_shim("cos", "Cos", &["X"]),
+        StdProc::Tan => create_math_shim("tan", "Tan", &["X"]),
+        StdProc::ArcSin => create_math_shim("arcsin", "ArcSin", &["X"]),
+        StdProc::ArcCos => create_math_shim("arccos", "ArcCos", &["X"]),
+        StdProc::ArcTan => create_math_shim("arctan", "ArcTan", &["x", "y"]),
+        StdProc::Sqrt => create_math_shim("sqrt", "Sqrt", &["A"]),
+        StdProc::Log => create_math_shim("log", "Log", &["X", "Y"]),
+        StdProc::Round => create_math_shim("round", "Round", &["A", "B"]),
+        StdProc::Floor => create_math_shim("floor", "Floor", &["A"]),
+        StdProc::Ceil => create_math_shim("ceil", "Ceil", &["A"]),
+        StdProc::Trunc => create_math_shim("trunc", "Trunc", &["A"]),
+        StdProc::Fract => create_math_shim("fract", "Fract", &["A"]),
+        StdProc::Clamp => create_math_shim("clamp", "Clamp", &["Value", "Low", "High"]),
+        StdProc::Sign => create_math_shim("sign", "Sign", &["A"]),
+        StdProc::Lerp => create_math_shim("lerp", "Lerp", &["A", "B", "factor"]),
+        StdProc::Rand => create_random_shim("rand", "Rand", &["L", "H"]),
+        StdProc::Prob => create_random_shim("prob", "Prob", &["P"]),
+        StdProc::Pick => create_variadic_shim("pick", "object [DM]DM.DmRandom::Pick(object[])"),
+        StdProc::Roll => create_random_shim("roll", "Roll", &["ndice", "sides"]),
+        StdProc::RandSeed => create_random_shim("rand_seed", "RandSeed", &["Seed"]),
+        StdProc::JsonEncode => create_runtime_shim(
+            "json_encode",
+            &["Value", "flags"],
+            &runtime_target("DmJson", "Encode", 2),
+        ),
+        StdProc::JsonDecode => create_runtime_shim(
+            "json_decode",
+            &["JSON", "flags"],
+            &runtime_target("DmJson", "Decode", 2),
+        ),
+        StdProc::Md5 => create_encoding_shim("md5", "Md5", &["T"]),
+        StdProc::Sha1 => create_encoding_shim("sha1", "Sha1", &["T"]),
+        StdProc::UrlEncode => {
+            create_encoding_shim("url_encode", "UrlEncode", &["PlainText", "format"])
+        }
+        StdProc::UrlDecode => create_encoding_shim("url_decode", "UrlDecode", &["UrlText"]),
+        StdProc::List2Params => create_encoding_shim("list2params", "List2Params", &["List"]),
+        StdProc::Params2List => create_encoding_shim("params2list", "Params2List", &["Params"]),
+        StdProc::CKey => create_encoding_shim("ckey", "CKey", &["Key"]),
+        StdProc::CKeyEx => create_encoding_shim("ckeyEx", "CKeyEx", &["Text"]),
+        StdProc::HtmlEncode => create_encoding_shim("html_encode", "HtmlEncode", &["PlainText"]),
+        StdProc::HtmlDecode => create_encoding_shim("html_decode", "HtmlDecode", &["HtmlText"]),
+        StdProc::Rgb => create_runtime_shim(
+            "rgb",
+            &["R", "G", "B", "A"],
+            &runtime_target("DmColor", "Rgb", 4),
+        ),
+        StdProc::Rgb2Num => create_runtime_shim(
+            "rgb2num",
+            &["color", "space"],
+            &runtime_target("DmColor", "Rgb2Num", 2),
+        ),
+        StdProc::File => create_file_shim("file", "File", &["Path"]),
+        StdProc::File2Text => create_file_shim("file2text", "File2Text", &["File"]),
+        StdProc::Text2File => create_file_shim("text2file", "Text2File", &["Text", "File"]),
+        StdProc::FExists => create_file_shim("fexists", "FExists", &["File"]),
+        StdProc::FDel => create_file_shim("fdel", "FDel", &["File"]),
+        StdProc::FCopy => create_file_shim("fcopy", "FCopy", &["Src", "Dst"]),
+        StdProc::FList => create_file_shim("flist", "FList", &["Path"]),
+        StdProc::Regex => create_runtime_shim(
+            "regex",
+            &["pattern", "flags"],
+            &runtime_target("DmRegex", "Create", 2),
+        ),
+        StdProc::RegexNew => create_instance_shim(
+            "New",
+            &["pattern", "flags"],
+            &runtime_target("DmRegex", "New", 3),
+        ),
+        StdProc::RegexFind => create_instance_shim(
+            "Find",
+            &["haystack", "Start", "End"],
+            &runtime_target("DmRegex", "Find", 4),
+        ),
+        StdProc::RegexReplace => create_instance_shim(
+            "Replace",
+            &["haystack", "replacement", "Start", "End"],
+            &runtime_target("DmRegex", "Replace", 5),
+        ),
+        StdProc::SavefileNew => create_instance_shim(
+            "New",
+            &["filename", "timeout"],
+            &runtime_target("DmSavefile", "New", 3),
+        ),
+        StdProc::SavefileExportText => create_instance_shim(
+            "ExportText",
+            &["path", "file"],
+            &runtime_target("DmSavefile", "ExportText", 3),
+        ),
+        StdProc::SavefileImportText => create_instance_shim(
+            "ImportText",
+            &["path", "source"],
+            &runtime_target("DmSavefile", "ImportText", 3),
+        ),
+        StdProc::SavefileFlush => {
+            create_instance_shim("Flush", &[], &runtime_target("DmSavefile", "Flush", 1))
+        }
+        StdProc::WorldReboot => {
+            create_instance_shim("Reboot", &["reason"], &runtime_target("DmWorld", "Reboot", 2))
+        }
+        StdProc::WorldExport => create_instance_shim(
+            "Export",
+            &["Addr", "File", "Persist", "Clients"],
+            &runtime_target("DmExport", "Export", 5),
+        ),
+        StdProc::WorldImport => {
+            create_instance_shim("Import", &[], &runtime_target("DmExport", "Import", 1))
+        }
+        StdProc::WorldShellEo => {
+            create_instance_shim("shelleo", &["command"], &runtime_target("DmShell", "ShellEo", 2))
         }
         StdProc::WorldOutput => {
             let mut method = Method::new(
@@ -162,51 +522,87 @@ pub fn create_std_proc(std_proc: &StdProc) -> Method {
                 false,
             );
             method.code.instruction(Instruction::ldarg1);
+            write_stringify(&mut method.code);
             method.code.instruction(Instruction::call(
-                "void [mscorlib]System.Console::WriteLine(object)".to_owned(),
+                "void [mscorlib]System.Console::WriteLine(string)".to_owned(),
             ));
             method.code.instruction(Instruction::ldnull);
             method.code.instruction(Instruction::ret);
 
             method.maxstack = 1;
-            method.params.push(MethodParameter {
-                custom_attributes: vec![],
-                name: "obj".to_owned(),
-                type_name: "object".to_owned(),
-            });
+            method.params.push(MethodParameter::new("obj", "object"));
 
             method
         }
-        StdProc::Sin => {
-            let mut method = Method::new(
-                "sin".into(),
-                "object".into(),
-                MethodAccessibility::Public,
-                MethodVirtuality::NotVirtual,
-                InstructionBlob::default(),
-                true,
-            );
-            method.code.instruction(Instruction::ldarg0);
-            method
-                .code
-                .instruction(Instruction::unboxany("[mscorlib]System.Single".into()));
-            method.code.instruction(Instruction::convr8);
-            method.code.instruction(Instruction::call(
-                "float64 [mscorlib]System.Math::Sin(float64)".into(),
-            ));
-            method.code.instruction(Instruction::convr4);
-            method
-                .code
-                .instruction(Instruction::_box("[mscorlib]System.Single".into()));
-            method.code.instruction(Instruction::ret);
-
-            method.params.push(MethodParameter::new("X", "object"));
-            method.maxstack = 1;
-            method
+        StdProc::Min => create_variadic_shim("min", "object [DM]DM.DmMath::Min(object[])"),
+        StdProc::Max => create_variadic_shim("max", "object [DM]DM.DmMath::Max(object[])"),
+        StdProc::Length { chars } => create_text_shim("length", "Length", *chars, &["E"]),
+        StdProc::CopyText { chars } => {
+            create_text_shim("copytext", "CopyText", *chars, &["T", "Start", "End"])
+        }
+        StdProc::FindText { chars } => {
+            create_text_shim("findtext", "FindText", *chars, &FIND_PARAMS)
+        }
+        StdProc::FindTextEx { chars } => {
+            create_text_shim("findtextEx", "FindTextEx", *chars, &FIND_PARAMS)
+        }
+        StdProc::FindLastText { chars } => {
+            create_text_shim("findlasttext", "FindLastText", *chars, &FIND_PARAMS)
+        }
+        StdProc::FindLastTextEx { chars } => {
+            create_text_shim("findlasttextEx", "FindLastTextEx", *chars, &FIND_PARAMS)
+        }
+        StdProc::ReplaceText { chars } => {
+            create_text_shim("replacetext", "ReplaceText", *chars, &REPLACE_PARAMS)
+        }
+        StdProc::ReplaceTextEx { chars } => {
+            create_text_shim("replacetextEx", "ReplaceTextEx", *chars, &REPLACE_PARAMS)
         }
-        StdProc::Cos => {
+        StdProc::SplitText { chars } => {
+            create_text_shim("splittext", "SplitText", *chars, &SPLIT_PARAMS)
+        }
+        StdProc::Text2Ascii { chars } => {
+            create_text_shim("text2ascii", "Text2Ascii", *chars, &["T", "pos"])
+        }
+        StdProc::SpanText { chars } => {
+            create_text_shim("spantext", "SpanText", *chars, &SPAN_PARAMS)
+        }
+        StdProc::NonSpanText { chars } => {
+            create_text_shim("nonspantext", "NonSpanText", *chars, &SPAN_PARAMS)
+        }
+        StdProc::JoinText => create_text_shim("jointext", "JoinText", false, &JOIN_PARAMS),
+        StdProc::UpperText => create_text_shim("uppertext", "UpperText", false, &["T"]),
+        StdProc::LowerText => create_text_shim("lowertext", "LowerText", false, &["T"]),
+        StdProc::Ascii2Text => create_text_shim("ascii2text", "Ascii2Text", false, &["N"]),
+        StdProc::TrimText => create_text_shim("trimtext", "TrimText", false, &["Text"]),
+        StdProc::Num2Text => create_text_shim("num2text", "Num2Text", false, &["N", "A", "B"]),
+        StdProc::Text2Num => create_text_shim("text2num", "Text2Num", false, &["T", "radix"]),
+        StdProc::CmpText => {
+            create_variadic_shim("cmptext", "object [DM]DM.DmText::CmpText(object[])")
+        }
+        StdProc::SortText => {
+            create_variadic_shim("sorttext", "object [DM]DM.DmText::SortText(object[])")
+        }
+        StdProc::Text2Path => create_runtime_shim(
+            "text2path",
+            &["T"],
+            "class [DM]DM.DmPath [DM]DM.DmPath::FromText(object)",
+        ),
+        StdProc::IsType => create_runtime_shim(
+            "istype",
+            &["Val", "Type"],
+            "object [DM]DM.DmTypes::IsType(object, object)",
+        ),
+        StdProc::IsPath => create_runtime_shim(
+            "ispath",
+            &["Val", "Type"],
+            "object [DM]DM.DmTypes::IsPath(object, object)",
+        ),
+        StdProc::IsNum => create_isinst_proc("isnum", "[mscorlib]System.Single"),
+        StdProc::IsText => create_isinst_proc("istext", "[mscorlib]System.String"),
+        StdProc::IsNull => {
             let mut method = Method::new(
-                "cos".into(),
+                "isnull".into(),
                 "object".into(),
                 MethodAccessibility::Public,
                 MethodVirtuality::NotVirtual,
@@ -214,49 +610,48 @@ pub fn create_std_proc(std_proc: &StdProc) -> Method {
                 true,
             );
             method.code.instruction(Instruction::ldarg0);
-            method
-                .code
-                .instruction(Instruction::unboxany("[mscorlib]System.Single".into()));
-            method.code.instruction(Instruction::convr8);
+            method.code.instruction(Instruction::ldnull);
+            method.code.instruction(Instruction::ceq);
             method.code.instruction(Instruction::call(
-                "float64 [mscorlib]System.Math::Cos(float64)".into(),
+                "object [DM]DM.DmInternal::Bool(bool)".into(),
             ));
-            method.code.instruction(Instruction::convr4);
-            method
-                .code
-                .instruction(Instruction::_box("[mscorlib]System.Single".into()));
             method.code.instruction(Instruction::ret);
 
-            method.params.push(MethodParameter::new("X", "object"));
-            method.maxstack = 1;
+            method.params.push(MethodParameter::new("Val", "object"));
+            method.maxstack = 2;
             method
-        } /*
-        "min" => {
-        method.code.instruction(Instruction::ldarg0);
-        method.code.instruction(Instruction::unboxany("[mscorlib]System.Single".to_owned()));
-        method.code.instruction(Instruction::ldarg1);
-        method.code.instruction(Instruction::unboxany("[mscorlib]System.Single".to_owned()));
-        method.code.instruction(Instruction::call("float32 [mscorlib]System.Math::Min(float32, float32)".to_owned()));
-        method.code.instruction(Instruction::_box("[mscorlib]System.Single".to_owned()));
-        method.code.instruction(Instruction::ret);
-
-        method.params.push(MethodParameter::new("A", "object"));
-        method.params.push(MethodParameter::new("B", "object"));
-        method.maxstack = 2;
-        },
-        "max" => {
-        method.code.instruction(Instruction::ldarg0);
-        method.code.instruction(Instruction::unboxany("[mscorlib]System.Single".to_owned()));
-        method.code.instruction(Instruction::ldarg1);
-        method.code.instruction(Instruction::unboxany("[mscorlib]System.Single".to_owned()));
-        method.code.instruction(Instruction::call("float32 [mscorlib]System.Math::Max(float32, float32)".to_owned()));
-        method.code.instruction(Instruction::_box("[mscorlib]System.Single".to_owned()));
-        method.code.instruction(Instruction::ret);
-
-        method.params.push(MethodParameter::new("A", "object"));
-        method.params.push(MethodParameter::new("B", "object"));
-        method.maxstack = 2;
-        },*/
+        }
+        StdProc::IsList => create_isinst_proc("islist", "[DM]DM.DmList"),
+        StdProc::IsLoc => create_isinst_proc("isloc", "byond_root/atom"),
+        StdProc::IsTurf => create_isinst_proc("isturf", "byond_root/turf"),
+        StdProc::IsMob => create_isinst_proc("ismob", "byond_root/mob"),
+        StdProc::IsObj => create_isinst_proc("isobj", "byond_root/obj"),
+        StdProc::IsArea => create_isinst_proc("isarea", "byond_root/area"),
+        StdProc::IsIcon => create_isinst_proc("isicon", "byond_root/icon"),
+        StdProc::IsFile => create_isinst_proc("isfile", "[DM]DM.DmFile"),
+        StdProc::TypesOf => {
+            create_variadic_shim("typesof", "class [DM]DM.DmList [DM]DM.DmPath::TypesOf(object[])")
+        }
+        StdProc::SubtypesOf => create_variadic_shim(
+            "subtypesof",
+            "class [DM]DM.DmList [DM]DM.DmPath::SubtypesOf(object[])",
+        ),
+        StdProc::Alert => create_variadic_shim("alert", "object [DM]DM.DmConsole::Alert(object[])"),
+        StdProc::Shell => create_runtime_shim(
+            "shell",
+            &["command"],
+            &runtime_target("DmShell", "Shell", 1),
+        ),
+        StdProc::Time2Text => create_runtime_shim(
+            "time2text",
+            &["timestamp", "format", "timezone"],
+            &runtime_target("DmTime", "Time2Text", 3),
+        ),
+        StdProc::Sleep => create_runtime_shim(
+            "sleep",
+            &["Delay"],
+            "object [DM]DM.DmScheduler::Sleep(object)",
+        ),
         StdProc::Unimplemented(name) => {
             let mut method = Method::new(
                 name.clone(),
@@ -267,48 +662,204 @@ pub fn create_std_proc(std_proc: &StdProc) -> Method {
                 true,
             );
             method.code.not_implemented("std proc not implemented.");
+            method.params.push(MethodParameter::params_array("args"));
             method
         }
     }
 }
 
 pub fn create_std(state: &mut CompilerState) {
-    // Create global procs.
-    {
-        let mut proc_abs = Proc::new("abs", ProcSource::Std(StdProc::Abs));
-        proc_abs
-            .parameters
-            .push(ProcParameter::new("A", VariableType::Unspecified));
-        state.global_procs.insert(proc_abs.name.clone(), proc_abs);
-    }
+    // Math.
+    add_std_proc(state, "abs", StdProc::Abs, &["A"]);
+    add_variadic_std_proc(state, "min", StdProc::Min);
+    add_variadic_std_proc(state, "max", StdProc::Max);
+    add_std_proc(state, "sin", StdProc::Sin, &["X"]);
+    add_std_proc(state, "cos", StdProc::Cos, &["X"]);
+    add_std_proc(state, "tan", StdProc::Tan, &["X"]);
+    add_std_proc(state, "arcsin", StdProc::ArcSin, &["X"]);
+    add_std_proc(state, "arccos", StdProc::ArcCos, &["X"]);
+    add_std_proc(state, "arctan", StdProc::ArcTan, &["x", "y"]);
+    add_std_proc(state, "sqrt", StdProc::Sqrt, &["A"]);
+    add_std_proc(state, "log", StdProc::Log, &["X", "Y"]);
+    add_std_proc(state, "round", StdProc::Round, &["A", "B"]);
+    add_std_proc(state, "floor", StdProc::Floor, &["A"]);
+    add_std_proc(state, "ceil", StdProc::Ceil, &["A"]);
+    add_std_proc(state, "trunc", StdProc::Trunc, &["A"]);
+    add_std_proc(state, "fract", StdProc::Fract, &["A"]);
+    add_std_proc(state, "clamp", StdProc::Clamp, &["Value", "Low", "High"]);
+    add_std_proc(state, "sign", StdProc::Sign, &["A"]);
+    add_std_proc(state, "lerp", StdProc::Lerp, &["A", "B", "factor"]);
+
+    // Random numbers.
+    add_std_proc(state, "rand", StdProc::Rand, &["L", "H"]);
+    add_std_proc(state, "prob", StdProc::Prob, &["P"]);
+    add_variadic_std_proc(state, "pick", StdProc::Pick);
+    add_std_proc(state, "roll", StdProc::Roll, &["ndice", "sides"]);
+    add_std_proc(state, "rand_seed", StdProc::RandSeed, &["Seed"]);
 
+    // JSON.
+    add_std_proc(state, "json_encode", StdProc::JsonEncode, &["Value", "flags"]);
+    add_std_proc(state, "json_decode", StdProc::JsonDecode, &["JSON", "flags"]);
     {
-        let mut proc_min = Proc::new("min", ProcSource::Std(StdProc::Abs));
-        proc_min.var_arg = true;
-        state.global_procs.insert(proc_min.name.clone(), proc_min);
+        let mut pretty_print = GlobalVar::new("JSON_PRETTY_PRINT", &VariableType::Unspecified);
+        pretty_print.initializer = Some(VariableInitializer::Constant(Constant::Int(1)));
+        pretty_print.mutability = VariableMutability::Constant;
+        state.global_vars.insert(pretty_print.name.clone(), pretty_print);
     }
 
+    // Hashing and encoding.
+    add_std_proc(state, "md5", StdProc::Md5, &["T"]);
+    add_std_proc(state, "sha1", StdProc::Sha1, &["T"]);
+    add_std_proc(state, "url_encode", StdProc::UrlEncode, &["PlainText", "format"]);
+    add_std_proc(state, "url_decode", StdProc::UrlDecode, &["UrlText"]);
+    add_std_proc(state, "list2params", StdProc::List2Params, &["List"]);
+    add_std_proc(state, "params2list", StdProc::Params2List, &["Params"]);
+    add_std_proc(state, "ckey", StdProc::CKey, &["Key"]);
+    add_std_proc(state, "ckeyEx", StdProc::CKeyEx, &["Text"]);
+    add_std_proc(state, "html_encode", StdProc::HtmlEncode, &["PlainText"]);
+    add_std_proc(state, "html_decode", StdProc::HtmlDecode, &["HtmlText"]);
+
+    // Colors.
+    add_std_proc(state, "rgb", StdProc::Rgb, &["R", "G", "B", "A"]);
+    add_std_proc(state, "rgb2num", StdProc::Rgb2Num, &["color", "space"]);
+
+    // Files.
+    add_std_proc(state, "file", StdProc::File, &["Path"]);
+    add_std_proc(state, "file2text", StdProc::File2Text, &["File"]);
+    add_std_proc(state, "text2file", StdProc::Text2File, &["Text", "File"]);
+    add_std_proc(state, "fexists", StdProc::FExists, &["File"]);
+    add_std_proc(state, "fdel", StdProc::FDel, &["File"]);
+    add_std_proc(state, "fcopy", StdProc::FCopy, &["Src", "Dst"]);
+    add_std_proc(state, "flist", StdProc::FList, &["Path"]);
+
+    // Regular expressions.
+    add_std_proc(state, "regex", StdProc::Regex, &["pattern", "flags"]);
     {
-        let mut proc_max = Proc::new("max", ProcSource::Std(StdProc::Abs));
-        proc_max.var_arg = true;
-        state.global_procs.insert(proc_max.name.clone(), proc_max);
+        let regex_path: ByondPath = "/regex".into();
+        let mut regex_type = CompilerType::new(&regex_path);
+        regex_type.parent = Some("/datum".into());
+        for var_name in &["name", "flags", "text", "match", "index", "next", "group"] {
+            let type_var = TypeVar::new(var_name, &VariableType::Unspecified, true);
+            regex_type.vars.insert((*var_name).to_owned(), type_var);
+        }
+
+        add_type_std_proc(&mut regex_type, "New", StdProc::RegexNew, &["pattern", "flags"]);
+        add_type_std_proc(
+            &mut regex_type,
+            "Find",
+            StdProc::RegexFind,
+            &["haystack", "Start", "End"],
+        );
+        add_type_std_proc(
+            &mut regex_type,
+            "Replace",
+            StdProc::RegexReplace,
+            &["haystack", "replacement", "Start", "End"],
+        );
+
+        state.types.insert(regex_path, regex_type);
     }
 
+    // Savefiles.
     {
-        let mut proc_sin = Proc::new("sin", ProcSource::Std(StdProc::Sin));
-        proc_sin
-            .parameters
-            .push(ProcParameter::new("X", VariableType::Unspecified));
-        state.global_procs.insert(proc_sin.name.clone(), proc_sin);
+        let savefile_path: ByondPath = "/savefile".into();
+        let mut savefile_type = CompilerType::new(&savefile_path);
+        savefile_type.parent = Some("/datum".into());
+        for var_name in &["name", "cd", "dir"] {
+            let type_var = TypeVar::new(var_name, &VariableType::Unspecified, true);
+            savefile_type.vars.insert((*var_name).to_owned(), type_var);
+        }
+
+        add_type_std_proc(
+            &mut savefile_type,
+            "New",
+            StdProc::SavefileNew,
+            &["filename", "timeout"],
+        );
+        add_type_std_proc(
+            &mut savefile_type,
+            "ExportText",
+            StdProc::SavefileExportText,
+            &["path", "file"],
+        );
+        add_type_std_proc(
+            &mut savefile_type,
+            "ImportText",
+            StdProc::SavefileImportText,
+            &["path", "source"],
+        );
+        add_type_std_proc(&mut savefile_type, "Flush", StdProc::SavefileFlush, &[]);
+
+        state.types.insert(savefile_path, savefile_type);
     }
 
-    {
-        let mut proc_sin = Proc::new("cos", ProcSource::Std(StdProc::Cos));
-        proc_sin
-            .parameters
-            .push(ProcParameter::new("X", VariableType::Unspecified));
-        state.global_procs.insert(proc_sin.name.clone(), proc_sin);
+    add_std_proc(state, "text2path", StdProc::Text2Path, &["T"]);
+
+    // Type checks.
+    add_std_proc(state, "istype", StdProc::IsType, &["Val", "Type"]);
+    add_std_proc(state, "ispath", StdProc::IsPath, &["Val", "Type"]);
+    add_std_proc(state, "isnum", StdProc::IsNum, &["Val"]);
+    add_std_proc(state, "istext", StdProc::IsText, &["Val"]);
+    add_std_proc(state, "isnull", StdProc::IsNull, &["Val"]);
+    add_std_proc(state, "islist", StdProc::IsList, &["Object"]);
+    add_std_proc(state, "isloc", StdProc::IsLoc, &["Loc"]);
+    add_std_proc(state, "isturf", StdProc::IsTurf, &["Loc"]);
+    add_std_proc(state, "ismob", StdProc::IsMob, &["Loc"]);
+    add_std_proc(state, "isobj", StdProc::IsObj, &["Loc"]);
+    add_std_proc(state, "isarea", StdProc::IsArea, &["Loc"]);
+    add_std_proc(state, "isicon", StdProc::IsIcon, &["Icon"]);
+    add_std_proc(state, "isfile", StdProc::IsFile, &["File"]);
+
+    // Reflection.
+    add_variadic_std_proc(state, "typesof", StdProc::TypesOf);
+    add_variadic_std_proc(state, "subtypesof", StdProc::SubtypesOf);
+
+    // Text.
+    for &chars in &[false, true] {
+        let suffix = if chars { "_char" } else { "" };
+        let name = |base: &str| format!("{}{}", base, suffix);
+
+        add_std_proc(state, &name("length"), StdProc::Length { chars }, &["E"]);
+        add_std_proc(state, &name("copytext"), StdProc::CopyText { chars }, &["T", "Start", "End"]);
+        add_std_proc(state, &name("findtext"), StdProc::FindText { chars }, &FIND_PARAMS);
+        add_std_proc(state, &name("findtextEx"), StdProc::FindTextEx { chars }, &FIND_PARAMS);
+        add_std_proc(state, &name("findlasttext"), StdProc::FindLastText { chars }, &FIND_PARAMS);
+        add_std_proc(
+            state,
+            &name("findlasttextEx"),
+            StdProc::FindLastTextEx { chars },
+            &FIND_PARAMS,
+        );
+        add_std_proc(state, &name("replacetext"), StdProc::ReplaceText { chars }, &REPLACE_PARAMS);
+        add_std_proc(
+            state,
+            &name("replacetextEx"),
+            StdProc::ReplaceTextEx { chars },
+            &REPLACE_PARAMS,
+        );
+        add_std_proc(state, &name("splittext"), StdProc::SplitText { chars }, &SPLIT_PARAMS);
+        add_std_proc(state, &name("text2ascii"), StdProc::Text2Ascii { chars }, &["T", "pos"]);
+        add_std_proc(state, &name("spantext"), StdProc::SpanText { chars }, &SPAN_PARAMS);
+        add_std_proc(state, &name("nonspantext"), StdProc::NonSpanText { chars }, &SPAN_PARAMS);
     }
+    add_std_proc(state, "jointext", StdProc::JoinText, &JOIN_PARAMS);
+    add_std_proc(state, "uppertext", StdProc::UpperText, &["T"]);
+    add_std_proc(state, "lowertext", StdProc::LowerText, &["T"]);
+    add_std_proc(state, "ascii2text", StdProc::Ascii2Text, &["N"]);
+    add_std_proc(state, "trimtext", StdProc::TrimText, &["Text"]);
+    add_std_proc(state, "num2text", StdProc::Num2Text, &["N", "A", "B"]);
+    add_std_proc(state, "text2num", StdProc::Text2Num, &["T", "radix"]);
+    add_variadic_std_proc(state, "cmptext", StdProc::CmpText);
+    add_variadic_std_proc(state, "sorttext", StdProc::SortText);
+
+    add_std_proc(state, "sleep", StdProc::Sleep, &["Delay"]);
+    add_std_proc(state, "time2text", StdProc::Time2Text, &["timestamp", "format", "timezone"]);
+
+    // Talking to whoever runs the program, on the console.
+    add_variadic_std_proc(state, "alert", StdProc::Alert);
+
+    // Only allowed when the program is run with -trusted.
+    add_std_proc(state, "shell", StdProc::Shell, &["command"]);
 
     // Create world.
     {
@@ -320,7 +871,29 @@ pub fn create_std(state: &mut CompilerState) {
         output_proc
             .parameters
             .push(ProcParameter::new("O", VariableType::Unspecified));
+        output_proc.owner = Some(world_path.clone());
         world_type.procs.insert("output".into(), output_proc);
+        add_type_std_proc(&mut world_type, "Reboot", StdProc::WorldReboot, &["reason"]);
+        add_type_std_proc(
+            &mut world_type,
+            "Export",
+            StdProc::WorldExport,
+            &["Addr", "File", "Persist", "Clients"],
+        );
+        add_type_std_proc(&mut world_type, "Import", StdProc::WorldImport, &[]);
+        add_type_std_proc(&mut world_type, "shelleo", StdProc::WorldShellEo, &["command"]);
+
+        // Kept up to date by DmWorld. log defaults to stderr, params and port come from the command line.
+        let world_vars = [
+            "time", "timeofday", "realtime", "tick_lag", "fps", "log", "name", "params", "port",
+            "address",
+        ];
+        for var_name in &world_vars {
+            let type_var = TypeVar::new(var_name, &VariableType::Unspecified, true);
+            world_type.vars.insert((*var_name).to_owned(), type_var);
+        }
+        set_var_constant(&mut world_type, "tick_lag", Constant::Int(1));
+        set_var_constant(&mut world_type, "fps", Constant::Int(10));
 
         state.types.insert(world_path.clone(), world_type);
 
@@ -328,4 +901,60 @@ pub fn create_std(state: &mut CompilerState) {
         world_var.mutability = VariableMutability::Readonly;
         state.global_vars.insert("world".into(), world_var);
     }
+
+    // Runtime errors are passed to world/Error() as one of these.
+    {
+        let exception_path: ByondPath = "/exception".into();
+        let mut exception_type = CompilerType::new(&exception_path);
+        exception_type.parent = Some("/datum".into());
+        for var_name in &["name", "desc", "file", "line"] {
+            let type_var = TypeVar::new(var_name, &VariableType::Unspecified, true);
+            exception_type.vars.insert((*var_name).to_owned(), type_var);
+        }
+
+        state.types.insert(exception_path, exception_type);
+    }
+}
+
+/// Sets the initial value of a var declared on a std type.
+fn set_var_constant(compiler_type: &mut CompilerType, name: &str, value: Constant) {
+    if let Some(type_var) = compiler_type.vars.get_mut(name) {
+        type_var.initializer = Some(VariableInitializer::Constant(value));
+    }
+}
+
+/// Registers a std proc that takes any number of arguments, as an array.
+fn add_variadic_std_proc(state: &mut CompilerState, name: &str, std_proc: StdProc) {
+    let mut new_proc = Proc::new(name, ProcSource::Std(std_proc));
+    new_proc.var_arg = true;
+    state.global_procs.insert(name.to_owned(), new_proc);
+}
+
+/// Registers a global std proc with untyped parameters.
+fn add_std_proc(state: &mut CompilerState, name: &str, std_proc: StdProc, params: &[&str]) {
+    state
+        .global_procs
+        .insert(name.to_owned(), create_untyped_std_proc(name, std_proc, params));
+}
+
+/// Registers a std proc with untyped parameters on a builtin type.
+fn add_type_std_proc(
+    compiler_type: &mut CompilerType,
+    name: &str,
+    std_proc: StdProc,
+    params: &[&str],
+) {
+    let mut new_proc = create_untyped_std_proc(name, std_proc, params);
+    new_proc.owner = Some(compiler_type.path.clone());
+    compiler_type.procs.insert(name.to_owned(), new_proc);
+}
+
+fn create_untyped_std_proc(name: &str, std_proc: StdProc, params: &[&str]) -> Proc {
+    let mut new_proc = Proc::new(name, ProcSource::Std(std_proc));
+    for param in params {
+        new_proc
+            .parameters
+            .push(ProcParameter::new(param, VariableType::Unspecified));
+    }
+    new_proc
 }
@@ -2,19 +2,20 @@ use dreammaker::indents::IndentProcessor;
 use dreammaker::objtree::ObjectTree;
 use dreammaker::parser::Parser;
 use dreammaker::preprocessor::Preprocessor;
-use dreammaker::Context;
+use dreammaker::{Context, FileId};
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Handles storage of the DM Object/Syntax trees and such.
 pub(crate) struct DMState {
+    context: Context,
     tree: ObjectTree,
 }
 
 impl DMState {
     pub fn load<P: AsRef<Path>>(path: P) -> io::Result<DMState> {
+        let context = Context::default();
         let tree = {
-            let context = Context::default();
             let preprocess = Preprocessor::new(&context, path.as_ref().to_owned())?;
             let indents = IndentProcessor::new::<Preprocessor>(&context, preprocess);
             let mut parser = Parser::new(&context, indents);
@@ -24,10 +25,15 @@ impl DMState {
             tree
         };
 
-        Ok(DMState { tree })
+        Ok(DMState { context, tree })
     }
 
     pub fn get_tree(&self) -> &ObjectTree {
         &self.tree
     }
+
+    /// Gets the path of a source file, for runtime errors.
+    pub fn file_path(&self, file: FileId) -> PathBuf {
+        self.context.file_path(file)
+    }
 }
@@ -63,6 +63,11 @@ impl Class {
         self.methods.insert(method.name.to_owned(), method)
     }
 
+    /// Inserts a method under a different key than its name, so it can overload another method.
+    pub fn insert_overload(&mut self, key: String, method: Method) -> Option<Method> {
+        self.methods.insert(key, method)
+    }
+
     pub fn has_child_class(&self, name: &str) -> bool {
         self.children.contains_key(name)
     }
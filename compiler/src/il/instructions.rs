@@ -64,9 +64,11 @@ pub enum Instruction {
     castclass(String),
     ceq,
     cgt,
+    cgtun,
     convr4,
     convr8,
     dup,
+    isinst(String),
     ldarg(u16),
     ldarg0,
     ldarg1,
@@ -74,7 +76,9 @@ pub enum Instruction {
     ldci40,
     ldci41,
     ldcr4(f32),
+    ldelemref,
     ldfld(String),
+    ldftn(String),
     ldloc(u16),
     ldloc0,
     ldnull,
@@ -87,6 +91,7 @@ pub enum Instruction {
     pop,
     ret,
     stelemref,
+    stfld(String),
     stloc(u16),
     stloc0,
     stsfld(String),
@@ -109,9 +114,11 @@ impl fmt::Display for Instruction {
             castclass(class) => write!(f, "castclass {}", class),
             ceq => write!(f, "ceq"),
             cgt => write!(f, "cgt"),
+            cgtun => write!(f, "cgt.un"),
             convr4 => write!(f, "conv.r4"),
             convr8 => write!(f, "conv.r8"),
             dup => write!(f, "dup"),
+            isinst(class) => write!(f, "isinst {}", class),
             ldarg(num) => write!(f, "ldarg {}", num),
             ldarg0 => write!(f, "ldarg.0"),
             ldarg1 => write!(f, "ldarg.1"),
@@ -119,7 +126,9 @@ impl fmt::Display for Instruction {
             ldci41 => write!(f, "ldc.i4.1"),
             ldci4(num) => write!(f, "ldc.i4 {}", num),
             ldcr4(num) => write!(f, "ldc.r4 {}", num),
+            ldelemref => write!(f, "ldelem.ref"),
             ldfld(field) => write!(f, "ldfld {}", field),
+            ldftn(method) => write!(f, "ldftn {}", method),
             ldloc0 => write!(f, "ldloc.0"),
             ldloc(idx) => write!(f, "ldloc {}", idx),
             ldnull => write!(f, "ldnull"),
@@ -132,6 +141,7 @@ impl fmt::Display for Instruction {
             pop => write!(f, "pop"),
             ret => write!(f, "ret"),
             stelemref => write!(f, "stelem.ref"),
+            stfld(field) => write!(f, "stfld {}", field),
             stloc(idx) => write!(f, "stloc {}", idx),
             stloc0 => write!(f, "stloc.0"),
             stsfld(field) => write!(f, "stsfld {}", field),
@@ -142,7 +152,8 @@ impl fmt::Display for Instruction {
     }
 }
 
-fn escape_string(string: &str) -> String {
+/// Escapes a string for use in a CIL string literal.
+pub(crate) fn escape_string(string: &str) -> String {
     string
         .replace(r"\", r"\\")
         .replace(r#"""#, r#"\""#)
@@ -68,6 +68,9 @@ impl Method {
             if i != 0 {
                 write!(writer, ", ")?;
             }
+            if param.default_value.is_some() {
+                write!(writer, "[opt] ")?;
+            }
             write!(writer, "{} {}", param.type_name, param.name)?;
         }
 
@@ -77,6 +80,22 @@ impl Method {
         }
         writeln!(writer, ".maxstack {}", self.maxstack)?;
 
+        for (i, param) in self.params.iter().enumerate() {
+            if param.custom_attributes.len() == 0 && param.default_value.is_none() {
+                continue;
+            }
+
+            // Parameter 0 is the return value.
+            write!(writer, ".param [{}]", i + 1)?;
+            if let Some(default_value) = &param.default_value {
+                write!(writer, " = {}", default_value)?;
+            }
+            writeln!(writer)?;
+            for attribute in &param.custom_attributes {
+                writeln!(writer, ".custom {}", attribute)?;
+            }
+        }
+
         if self.locals.len() != 0 {
             write!(writer, ".locals init (")?;
             for (i, local) in self.locals.iter().enumerate() {
@@ -101,6 +120,8 @@ pub struct MethodParameter {
     pub name: String,
     pub type_name: String,
     pub custom_attributes: Vec<String>,
+    /// The constant used when the argument is left out, like `float32(1)`.
+    pub default_value: Option<String>,
 }
 
 impl MethodParameter {
@@ -109,8 +130,19 @@ impl MethodParameter {
             name: name.to_owned(),
             type_name: type_name.to_owned(),
             custom_attributes: vec![],
+            default_value: None,
         }
     }
+
+    /// An `object[]` parameter taking all remaining arguments, like C#'s `params object[]`.
+    pub fn params_array(name: &str) -> MethodParameter {
+        let mut param = MethodParameter::new(name, "object[]");
+        param.custom_attributes.push(
+            "instance void [mscorlib]System.ParamArrayAttribute::.ctor() = ( 01 00 00 00 )"
+                .to_owned(),
+        );
+        param
+    }
 }
 
 /// Method attributes corresponding to accessibility.
@@ -1,4 +1,7 @@
+use dreammaker::ast::{Block, Statement, Term};
+use dreammaker::objtree::{Code, TypeProc, TypeRef, VarValue};
 use dreammaker::{FileId, Location};
+use std::collections::HashMap;
 use std::fmt;
 use std::fs::File;
 use std::path::PathBuf;
@@ -31,7 +34,16 @@ fn main() -> std::io::Result<()> {
             .to_owned(),
     );
 
-    write_everything(&mut asm, &state, &compiler_state);
+    let has_main = compiler_state.global_procs.contains_key("main");
+    if opt.main && !has_main {
+        println!("ERROR: Compiled with --main, but there is no main() proc to start at.");
+        std::process::exit(1);
+    }
+    if !opt.main && has_main {
+        compiler_warning("main() is only called with --main, the program starts at world/New().");
+    }
+
+    write_everything(&mut asm, &state, &compiler_state, opt.main);
 
     let (il_path, mut handle) = get_il_file(&opt)?;
     asm.write(&mut handle)?;
@@ -94,6 +106,11 @@ struct Opt {
     /// Optional path to dump IL code to.
     #[structopt(long = "il")]
     il_path: Option<String>,
+
+    /// Start the program by calling the global main() proc instead of world/New(),
+    /// for script-style programs.
+    #[structopt(long = "main")]
+    main: bool,
 }
 
 fn create_everything(dm_state: &DMState) -> CompilerState {
@@ -111,23 +128,10 @@ fn create_everything(dm_state: &DMState) -> CompilerState {
             .declaration
             .as_ref()
             .expect("Global vars should have a declaration, right?");
-        let value = &var.value;
-        let var_type = if declaration.var_type.type_path.len() == 0 {
-            VariableType::Unspecified
-        } else {
-            VariableType::Object(ByondPath::new(&declaration.var_type.type_path, true))
-        };
-
-        let initializer = if let Some(constant) = &value.constant {
-            Some(VariableInitializer::Constant(constant.clone()))
-        } else if let Some(expr) = &value.expression {
-            Some(VariableInitializer::Expression(expr.clone()))
-        } else {
-            None
-        };
+        let var_type = VariableType::from_type_path(&declaration.var_type.type_path);
 
         let mut global_var = GlobalVar::new(&name, &var_type);
-        global_var.initializer = initializer;
+        global_var.initializer = get_var_initializer(&var.value);
         if declaration.var_type.is_const {
             global_var.mutability = VariableMutability::Constant;
         }
@@ -135,43 +139,173 @@ fn create_everything(dm_state: &DMState) -> CompilerState {
     }
 
     for (name, proc_type) in &tree_root.procs {
-        if proc_type.value.len() > 1 {
-            compiler_warning(format!("Skipping proc with multiple values: {}", &name));
-            continue;
+        if let Some(global_proc) = create_code_proc(name, proc_type, &state.global_procs) {
+            state.global_procs.insert(name.clone(), global_proc);
         }
+    }
 
-        let value = &proc_type.value[0];
+    create_types(&mut state, tree.root());
 
-        let source = if value.location.file == FileId::builtins() {
-            if !state.global_procs.contains_key(name) {
-                ProcSource::Std(StdProc::Unimplemented(name.clone()))
-            } else {
-                // Implemented std proc that already exists!
-                // Yay!
+    state
+}
+
+/// Recursively registers the children of a type in the object tree as compiler types.
+fn create_types(state: &mut CompilerState, parent: TypeRef) {
+    for child in parent.children() {
+        let child_type = child.get();
+        let path = ByondPath::from(child_type.path.as_str());
+
+        let mut compiler_type = state
+            .types
+            .remove(&path)
+            .unwrap_or_else(|| CompilerType::new(&path));
+
+        compiler_type.parent = child
+            .parent_type()
+            .map(|parent_type| ByondPath::from(parent_type.get().path.as_str()))
+            .filter(|parent_path| parent_path.segment_count() != 0);
+
+        for (name, var) in &child_type.vars {
+            // These are computed from the object itself.
+            if name == "type" || name == "parent_type" || name == "vars" {
                 continue;
             }
-        } else {
-            ProcSource::Code(value.location)
-        };
 
-        let mut global_proc = Proc::new(&name, source);
-        for param in &value.parameters {
-            let var_type = if param.var_type.type_path.len() > 0 {
-                VariableType::Object(ByondPath::new(&param.var_type.type_path, true))
-            } else {
-                VariableType::Unspecified
+            let mut type_var = match &var.declaration {
+                Some(declaration) => {
+                    let var_type = VariableType::from_type_path(&declaration.var_type.type_path);
+                    let mut type_var = TypeVar::new(&name, &var_type, true);
+                    if declaration.var_type.is_const {
+                        type_var.mutability = VariableMutability::Constant;
+                    }
+                    type_var.is_static = declaration.var_type.is_static;
+                    type_var.is_tmp = declaration.var_type.is_tmp;
+                    type_var
+                }
+                None => TypeVar::new(&name, &VariableType::Unspecified, false),
             };
-            let param = ProcParameter::new(&param.name, var_type);
-            global_proc.parameters.push(param);
+            type_var.initializer = get_var_initializer(&var.value);
+            compiler_type.vars.insert(name.clone(), type_var);
         }
 
-        state.global_procs.insert(name.clone(), global_proc);
+        for (name, proc_type) in &child_type.procs {
+            if let Some(mut type_proc) = create_code_proc(name, proc_type, &compiler_type.procs) {
+                type_proc.owner = Some(path.clone());
+                compiler_type.procs.insert(name.clone(), type_proc);
+            }
+        }
+
+        for grandchild in child.children() {
+            let name = ByondPath::from(grandchild.get().path.as_str())
+                .last_segment()
+                .to_owned();
+            if !compiler_type.children.contains(&name) {
+                compiler_type.children.push(name);
+            }
+        }
+
+        state.types.insert(path, compiler_type);
+        create_types(state, child);
     }
+}
 
-    state
+/// Gets the initial value of a var in the object tree, preferring the evaluated constant.
+fn get_var_initializer(value: &VarValue) -> Option<VariableInitializer> {
+    if let Some(constant) = &value.constant {
+        Some(VariableInitializer::Constant(constant.clone()))
+    } else if let Some(expr) = &value.expression {
+        Some(VariableInitializer::Expression(expr.clone()))
+    } else {
+        None
+    }
 }
 
-fn write_everything(asm: &mut Assembly, dm_state: &DMState, compiler_state: &CompilerState) {
+/// Creates the compiler representation of a proc from the object tree.
+/// Returns `None` if the proc should be skipped,
+/// such as a builtin proc that's already implemented by the std lib.
+fn create_code_proc(
+    name: &str,
+    proc_type: &TypeProc,
+    existing: &HashMap<String, Proc>,
+) -> Option<Proc> {
+    // Overriding a proc on the same type, like world/Error() over the builtin, adds another value.
+    // Without ..() only the last definition is ever called.
+    let code_values = proc_type
+        .value
+        .iter()
+        .filter(|value| value.location.file != FileId::builtins())
+        .count();
+    if code_values > 1 {
+        compiler_warning(format!("Only the last definition of a proc is used: {}", &name));
+    }
+
+    let value = proc_type.value.last()?;
+
+    let source = if value.location.file == FileId::builtins() {
+        if !existing.contains_key(name) {
+            ProcSource::Std(StdProc::Unimplemented(name.to_owned()))
+        } else {
+            // Implemented std proc that already exists!
+            // Yay!
+            return None;
+        }
+    } else {
+        ProcSource::Code(value.location)
+    };
+
+    let mut code_proc = Proc::new(&name, source);
+    // Procs can be called with any number of arguments, the extra ones end up in args.
+    code_proc.var_arg = true;
+    if let Code::Present(block) = &value.code {
+        read_proc_settings(block, &mut code_proc.settings);
+    }
+
+    for param in &value.parameters {
+        let var_type = VariableType::from_type_path(&param.var_type.type_path);
+        let mut proc_param = ProcParameter::new(&param.name, var_type);
+        proc_param.default = param.default.clone();
+        code_proc.parameters.push(proc_param);
+    }
+
+    Some(code_proc)
+}
+
+/// Reads the `set` statements at the top level of a proc body.
+fn read_proc_settings(block: &Block, settings: &mut ProcSettings) {
+    for statement in block {
+        let (name, value) = match &statement.elem {
+            Statement::Setting { name, value, .. } => (name, value),
+            _ => continue,
+        };
+
+        let term = proc_transpiler::expression_as_term(value);
+        match name.as_str() {
+            "background" => settings.background = setting_truthy(term),
+            "waitfor" => settings.waitfor = setting_truthy(term),
+            // Only verbs use these, and there are no clients to call verbs.
+            "name" | "desc" | "category" | "hidden" | "popup_menu" | "instant" | "src" => {
+                compiler_warning(format!("Verb settings are ignored: {}", name))
+            }
+            _ => compiler_warning(format!("Unsupported proc setting: {}", name)),
+        }
+    }
+}
+
+fn setting_truthy(term: Option<&Term>) -> bool {
+    match term {
+        Some(Term::Int(0)) | Some(Term::Null) => false,
+        Some(Term::Float(val)) => *val != 0.0,
+        Some(Term::String(string)) => string.len() != 0,
+        _ => true,
+    }
+}
+
+fn write_everything(
+    asm: &mut Assembly,
+    dm_state: &DMState,
+    compiler_state: &CompilerState,
+    start_at_main: bool,
+) {
     // Create externs.
     {
         let externs = asm.get_externs_mut();
@@ -193,6 +327,9 @@ fn write_everything(asm: &mut Assembly, dm_state: &DMState, compiler_state: &Com
         false,
     );
     stack.push("byond_root".to_owned());
+    // The global cctor registers every type path with the runtime,
+    // so it has to run before anything touches byond_root, not lazily.
+    class_root.set_before_field_init(false);
 
     // Create global vars.
     for (name, var) in &compiler_state.global_vars {
@@ -213,8 +350,25 @@ fn write_everything(asm: &mut Assembly, dm_state: &DMState, compiler_state: &Com
 
     {
         let mut code = InstructionBlob::default();
-        code.instruction(Instruction::call("object byond_root::main()".into()));
-        code.instruction(Instruction::pop);
+        if start_at_main {
+            // main() runs on the scheduler, so it can sleep.
+            code.instruction(Instruction::ldnull);
+            code.instruction(Instruction::ldftn("object byond_root::main(object[])".into()));
+            code.instruction(Instruction::newobj(
+                "instance void class [mscorlib]System.Func`2<object[], object>::.ctor(object, native int)"
+                    .into(),
+            ));
+            code.instruction(Instruction::ldarg0);
+            code.instruction(Instruction::call(
+                "void [DM]DM.DmWorld::RunMain(class [mscorlib]System.Func`2<object[], object>, string[])"
+                    .into(),
+            ));
+        } else {
+            // The global cctor has created the world by the time the entry point runs.
+            code.instruction(Instruction::ldsfld("object byond_root::world".into()));
+            code.instruction(Instruction::ldarg0);
+            code.instruction(Instruction::call("void [DM]DM.DmWorld::Run(object, string[])".into()));
+        }
         code.instruction(Instruction::ret);
         let mut entry_point = Method::new(
             "<>EntryPoint".into(),
@@ -225,7 +379,8 @@ fn write_everything(asm: &mut Assembly, dm_state: &DMState, compiler_state: &Com
             true,
         );
         entry_point.is_entry_point = true;
-        entry_point.maxstack = 1;
+        entry_point.params.push(MethodParameter::new("args", "string[]"));
+        entry_point.maxstack = 2;
         class_root.insert_method(entry_point);
     }
 
@@ -263,24 +418,83 @@ fn write_everything(asm: &mut Assembly, dm_state: &DMState, compiler_state: &Com
 }
 
 fn create_type(
-    _asm: &mut Assembly,
+    asm: &mut Assembly,
     compiler_type: &CompilerType,
     compiler_state: &CompilerState,
     dm_state: &DMState,
     type_stack: &mut Vec<String>,
 ) -> Class {
-    let parent_type_name = type_stack.join("/");
+    let nesting_name = type_stack.join("/");
+    let parent_type_name = match &compiler_type.parent {
+        Some(parent) => proc_transpiler::byond_path_to_class(parent),
+        None => "byond_root".to_owned(),
+    };
     let name = compiler_type.path.last_segment();
     let mut class = Class::new(
         name.into(),
         ClassAccessibility::NestedPublic,
         Some(parent_type_name.clone()),
-        format!("{}/{}", parent_type_name, name),
+        format!("{}/{}", nesting_name, name),
         false,
     );
 
-    // Make stock .ctor.
-    let ctor = dm_std::create_stock_ctor(&parent_type_name);
+    let mut var_names = compiler_type.vars.keys().collect::<Vec<&String>>();
+    var_names.sort_unstable();
+
+    for var_name in &var_names {
+        let type_var = &compiler_type.vars[*var_name];
+        if !type_var.is_declaration {
+            continue;
+        }
+
+        // var/static and var/global are shared by all instances,
+        // they get initialized along with the globals.
+        let mut field = Field::default();
+        field.name = type_var.name.clone();
+        field.type_name = "object".into();
+        field.is_static = type_var.is_static;
+        field.accessibility = FieldAccessibility::Public;
+        class.insert_field(field);
+    }
+
+    // Initialize vars declared or overriden by this type in the .ctor.
+    let mut var_init = InstructionBlob::default();
+    for var_name in &var_names {
+        let type_var = &compiler_type.vars[*var_name];
+        let declaring_type = match compiler_state.get_type_var(&compiler_type.path, var_name) {
+            Some((declaring_type, declaration)) if !declaration.is_static => declaring_type,
+            _ => continue,
+        };
+        let field_name = format!(
+            "object {}::'{}'",
+            proc_transpiler::byond_path_to_class(declaring_type),
+            var_name
+        );
+
+        let mut value = InstructionBlob::default();
+        match dm_std::load_initializer(
+            &type_var.initializer,
+            &mut class,
+            &format!("{}_init", var_name),
+            dm_state,
+            compiler_state,
+            &mut value,
+        ) {
+            // Fields start out null anyways.
+            Ok(false) => {}
+            Ok(true) => {
+                var_init.instruction(Instruction::ldarg0);
+                var_init.absord(value);
+                var_init.instruction(Instruction::stfld(field_name));
+            }
+            Err(error) => println!(
+                "ERROR in initializer for {}/{}: {}",
+                compiler_type.path, var_name, error
+            ),
+        }
+    }
+
+    let ctor = dm_std::create_type_ctor(&parent_type_name, var_init);
     class.insert_method(ctor);
 
     for (name, child_proc) in &compiler_type.procs {
@@ -290,7 +504,7 @@ fn create_type(
                 &child_proc,
                 &mut class,
                 &name,
-                true,
+                false,
                 dm_state,
                 &compiler_state,
             ),
@@ -306,6 +520,14 @@ fn create_type(
 
     type_stack.push(name.into());
 
+    for child_name in &compiler_type.children {
+        let child_path = compiler_type.path.child(child_name);
+        if let Some(child_type) = compiler_state.types.get(&child_path) {
+            let child_class = create_type(asm, child_type, compiler_state, dm_state, type_stack);
+            class.insert_child_class(child_class);
+        }
+    }
+
     type_stack.pop();
 
     class
@@ -23,58 +23,242 @@ pub(crate) fn create_proc(
             uniques: 0,
             state,
             compiler_state,
-            class,
+            class: &mut *class,
             proc_name,
             is_static,
+            type_path: if is_static {
+                None
+            } else {
+                the_proc.owner.clone()
+            },
+            is_background: the_proc.settings.background,
+            args_local: None,
             loop_labels: vec![],
         };
 
         let mut ins = InstructionBlob::default();
+        write_enter(the_proc, proc_name, is_static, state, &mut ins);
 
-        // Load up arguments into locals.
-        // Not efficient but it makes the code simpler.
+        // All arguments come in as one array, since DM procs can be called with any number of them.
+        // Load up the declared ones into locals, missing ones are null.
         for (i, param) in the_proc.parameters.iter().enumerate() {
-            let local = data.add_local(&param.name);
-            let arg = if is_static { i } else { i + 1 } as u16;
-            ins.instruction(Instruction::ldarg(arg));
+            let local = data.add_local(&param.name, param.var_type.clone());
+            ins.instruction(Instruction::ldarg(data.args_arg()));
+            ins.instruction(Instruction::ldci4(i as i32));
+            ins.instruction(Instruction::call(
+                "object [DM]DM.DmInternal::Arg(object[], int32)".to_owned(),
+            ));
             ins.instruction(Instruction::stloc(local));
         }
+        data.args_local = Some(data.add_unnamed_local());
+
+        // Defaults are evaluated when the proc is called, so they can use src and the other arguments.
+        for param in &the_proc.parameters {
+            if let Some(default) = &param.default {
+                let local = data.get_local(&param.name).unwrap();
+                let passed_label = format!("default_{}", data.get_uniq());
+                ins.instruction(Instruction::ldloc(local));
+                ins.instruction(Instruction::brtrue(passed_label.clone()));
+                evaluate_expression(default, &mut data, &mut ins)?;
+                ins.instruction(Instruction::stloc(local));
+                ins.label(passed_label);
+            }
+        }
 
         // Load null into . (default return value.)
         ins.instruction(Instruction::ldnull);
         ins.instruction(Instruction::stloc0);
-        for statement in code {
-            write_statement(&statement.elem, &mut data, &mut ins)?;
-        }
+        write_block(code, &mut data, &mut ins)?;
 
         ins.instruction(Instruction::ldloc0);
-        ins.instruction(Instruction::ret);
+        write_return(&mut ins);
+
+        let total_locals = data.total_locals;
+
+        // All DM procs on types can be overriden.
+        let virtuality = if is_static {
+            MethodVirtuality::NotVirtual
+        } else {
+            MethodVirtuality::Virtual
+        };
 
         let mut method = Method::new(
             proc_name.to_owned(),
             "object".into(),
             MethodAccessibility::Public,
-            MethodVirtuality::NotVirtual,
+            virtuality,
             ins,
             is_static,
         );
 
-        for param in &the_proc.parameters {
-            method
-                .params
-                .push(MethodParameter::new(&param.name, "object"));
-        }
+        // The declared parameters only exist as locals, so their .param defaults go on the typed entry point below.
+        method.params.push(MethodParameter::params_array("args"));
 
-        for _ in 0..data.total_locals {
+        for _ in 0..total_locals {
             method.locals.push("object".to_owned());
         }
 
+        // Only global procs get one: on a type, the dynamic binder would pick it over the args array one.
+        if is_static {
+            if let Some(entry_point) =
+                create_typed_entry_point(the_proc, proc_name, class.get_full_name())
+            {
+                class.insert_overload(format!("{}(typed)", proc_name), entry_point);
+            }
+        }
+
+        // waitfor = 0 procs run their body on a separate DM thread.
+        if !the_proc.settings.waitfor {
+            // The method we just made becomes the body, what callers see is a wrapper that starts it.
+            let body_name = format!("<>{}_detached", proc_name);
+            let wrapper = create_detached_wrapper(&method, &body_name, class.get_full_name());
+
+            method.name = body_name;
+            method.accessibility = MethodAccessibility::Private;
+            method.virtuality = MethodVirtuality::NotVirtual;
+            class.insert_method(method);
+
+            return Ok(wrapper);
+        }
+
         Ok(method)
     } else {
         Err(format!("Unable to find proc body: {}, {:?}", proc_name, the_proc).into())
     }
 }
 
+/// Creates a static method for .NET callers that takes the declared parameters of a global proc,
+/// with their constant defaults as .param metadata, and passes them on to the proc as its args array.
+/// Returns `None` if no parameter has a constant default.
+fn create_typed_entry_point(the_proc: &Proc, proc_name: &str, class_name: &str) -> Option<Method> {
+    let defaults = the_proc
+        .parameters
+        .iter()
+        .map(|param| param.default.as_ref().and_then(constant_param_default))
+        .collect::<Vec<_>>();
+    if defaults.iter().all(Option::is_none) {
+        return None;
+    }
+
+    let mut code = InstructionBlob::default();
+    code.instruction(Instruction::ldci4(defaults.len() as i32));
+    code.instruction(Instruction::newarr("[mscorlib]System.Object".to_owned()));
+    let mut params = vec![];
+    for (i, (param, default)) in the_proc.parameters.iter().zip(defaults).enumerate() {
+        code.instruction(Instruction::dup);
+        code.instruction(Instruction::ldci4(i as i32));
+        code.instruction(Instruction::ldarg(i as u16));
+        code.instruction(Instruction::stelemref);
+
+        let mut method_param = MethodParameter::new(&format!("'{}'", param.name), "object");
+        // Any argument can be left out, the proc evaluates non-constant defaults itself when it gets null.
+        method_param.default_value = Some(default.unwrap_or_else(|| "nullref".to_owned()));
+        params.push(method_param);
+    }
+    code.instruction(Instruction::call(format!(
+        "object {}::'{}'(object[])",
+        class_name, proc_name
+    )));
+    code.instruction(Instruction::ret);
+
+    let mut method = Method::new(
+        proc_name.to_owned(),
+        "object".to_owned(),
+        MethodAccessibility::Public,
+        MethodVirtuality::NotVirtual,
+        code,
+        true,
+    );
+    method.params = params;
+    method.maxstack = 4;
+    Some(method)
+}
+
+/// Gets the CIL representation of a parameter default, if it's a constant.
+fn constant_param_default(default: &Expression) -> Option<String> {
+    match expression_as_term(default)? {
+        Term::Null => Some("nullref".to_owned()),
+        // Debug formatting keeps the decimal point, float32(1) would be read as the bits of a float.
+        Term::Int(i) => Some(format!("float32({:?})", *i as f32)),
+        Term::Float(f) => Some(format!("float32({:?})", f)),
+        Term::String(s) => Some(format!("\"{}\"", escape_string(s))),
+        _ => None,
+    }
+}
+
+/// Pushes the call stack frame runtime errors use to say where they happened.
+fn write_enter(
+    the_proc: &Proc,
+    proc_name: &str,
+    is_static: bool,
+    state: &DMState,
+    ins: &mut InstructionBlob,
+) {
+    let proc_path = match &the_proc.owner {
+        Some(owner) => format!("{}/proc/{}", owner, proc_name),
+        None => format!("/proc/{}", proc_name),
+    };
+    let (file, line) = match &the_proc.source {
+        ProcSource::Code(location) => (
+            state.file_path(location.file).display().to_string(),
+            location.line,
+        ),
+        _ => (String::new(), 0),
+    };
+
+    ins.instruction(Instruction::ldstr(proc_name.to_owned()));
+    ins.instruction(Instruction::ldstr(proc_path));
+    ins.instruction(Instruction::ldstr(file));
+    ins.instruction(Instruction::ldci4(line as i32));
+    if is_static {
+        ins.instruction(Instruction::ldnull);
+    } else {
+        ins.instruction(Instruction::ldarg0);
+    }
+    ins.instruction(Instruction::call(
+        "void [DM]DM.DmError::Enter(string, string, string, int32, object)".to_owned(),
+    ));
+}
+
+/// Creates a method with the same signature as `method`,
+/// which runs the `body_name` method on a new DM thread with the same arguments array.
+/// The wrapper returns as soon as the body sleeps.
+fn create_detached_wrapper(method: &Method, body_name: &str, class_name: &str) -> Method {
+    let mut code = InstructionBlob::default();
+    if method.is_static {
+        code.instruction(Instruction::ldnull);
+    } else {
+        code.instruction(Instruction::ldarg0);
+    }
+    code.instruction(Instruction::ldftn(format!(
+        "{}object {}::'{}'(object[])",
+        if method.is_static { "" } else { "instance " },
+        class_name,
+        body_name
+    )));
+    code.instruction(Instruction::newobj(
+        "instance void class [mscorlib]System.Func`2<object[], object>::.ctor(object, native int)"
+            .to_owned(),
+    ));
+
+    code.instruction(Instruction::ldarg(if method.is_static { 0 } else { 1 }));
+
+    code.instruction(Instruction::call("object [DM]DM.DmScheduler::CallDetached(class [mscorlib]System.Func`2<object[], object>, object[])".to_owned()));
+    code.instruction(Instruction::ret);
+
+    let mut wrapper = Method::new(
+        method.name.clone(),
+        method.return_type.clone(),
+        method.accessibility,
+        method.virtuality,
+        code,
+        method.is_static,
+    );
+    wrapper.params = method.params.clone();
+    wrapper.maxstack = 3;
+    wrapper
+}
+
 pub(crate) fn evaluate_initializer(
     expression: &Expression,
     class: &mut Class,
@@ -92,22 +276,38 @@ pub(crate) fn evaluate_initializer(
         is_static: true,
         proc_name,
         class,
+        type_path: None,
+        is_background: false,
+        args_local: None,
         loop_labels: vec![],
     };
 
     evaluate_expression(expression, &mut data, blob)
 }
 
+/// Where a local variable is stored.
+enum LocalStorage {
+    Local(u16),
+    /// A field signature, for `var/static` in procs.
+    Static(String),
+}
+
 /// Shared data necessary across the entire proc transpile.
 struct TranspilerData<'a> {
     pub total_locals: u16,
-    pub locals: Vec<HashMap<String, u16>>,
+    pub locals: Vec<HashMap<String, (LocalStorage, VariableType)>>,
     pub uniques: u16,
     pub state: &'a DMState,
     pub compiler_state: &'a CompilerState,
     pub class: &'a mut Class,
     pub proc_name: &'a str,
     pub is_static: bool,
+    /// The type we're compiling an instance proc of, `None` in static procs.
+    pub type_path: Option<ByondPath>,
+    /// `set background = 1`, loops let other procs run if they take too long.
+    pub is_background: bool,
+    /// The local caching the `args` list, `None` outside of procs.
+    pub args_local: Option<u16>,
     pub loop_labels: Vec<(String, String)>,
 }
 
@@ -134,35 +334,87 @@ impl<'a> TranspilerData<'a> {
         name
     }
 
+    /// The index of the arguments array parameter.
+    pub fn args_arg(&self) -> u16 {
+        if self.is_static {
+            0
+        } else {
+            1
+        }
+    }
+
     pub fn get_uniq(&mut self) -> u16 {
         let val = self.uniques;
         self.uniques += 1;
         val
     }
 
-    /// Adds a local variable with specified name to this scope.
-    pub fn add_local(&mut self, name: &str) -> u16 {
+    /// Adds a local variable with specified name and declared type to this scope.
+    pub fn add_local(&mut self, name: &str, var_type: VariableType) -> u16 {
         // NOTE: Local 0 is . (default return value).
         let top_pos = self.locals.len() - 1;
         let top = &mut self.locals[top_pos];
         let new_local_id = self.total_locals;
         self.total_locals += 1;
-        top.insert(name.to_owned(), new_local_id);
+        top.insert(
+            name.to_owned(),
+            (LocalStorage::Local(new_local_id), var_type),
+        );
         new_local_id
     }
 
+    /// Adds a static field to the meta class, returning its signature.
+    pub fn add_meta_static_field(&mut self, name: &str, type_name: &str) -> String {
+        let meta_class = self.get_meta_class();
+        meta_class.insert_field(Field {
+            name: name.to_owned(),
+            type_name: type_name.to_owned(),
+            accessibility: FieldAccessibility::Public,
+            is_static: true,
+            is_initonly: false,
+        });
+        format!("{} {}::'{}'", type_name, meta_class.get_full_name(), name)
+    }
+
+    /// Adds a `var/static` or `var/global` to this scope, stored in a static field on the meta class.
+    /// Returns the field signature.
+    pub fn add_static_local(&mut self, name: &str, var_type: VariableType) -> String {
+        let field_name = format!("<>static_{}_{}", name, self.get_uniq());
+        let field = self.add_meta_static_field(&field_name, "object");
+
+        let top_pos = self.locals.len() - 1;
+        self.locals[top_pos].insert(
+            name.to_owned(),
+            (LocalStorage::Static(field.clone()), var_type),
+        );
+        field
+    }
+
+    fn find_local(&self, name: &str) -> Option<&(LocalStorage, VariableType)> {
+        self.locals.iter().rev().filter_map(|l| l.get(name)).next()
+    }
+
     /// Gets the ID of a local variable.
     pub fn get_local(&self, name: &str) -> Option<u16> {
-        for locals in self.locals.iter().rev() {
-            if let Some(id) = locals.get(name) {
-                return Some(*id);
-            }
+        match self.find_local(name) {
+            Some((LocalStorage::Local(id), _)) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Gets the field of a static local variable.
+    pub fn get_static_local(&self, name: &str) -> Option<&str> {
+        match self.find_local(name) {
+            Some((LocalStorage::Static(field), _)) => Some(field),
+            _ => None,
         }
+    }
 
-        None
+    /// Gets the declared type of a local variable.
+    pub fn get_local_type(&self, name: &str) -> Option<&VariableType> {
+        self.find_local(name).map(|(_, var_type)| var_type)
     }
 
-    #[allow(dead_code)]
     pub fn add_unnamed_local(&mut self) -> u16 {
         let new_local_id = self.total_locals;
         self.total_locals += 1;
@@ -205,6 +457,32 @@ impl<'a> TranspilerData<'a> {
     }
 }
 
+/// Writes the statements of a block,
+/// keeping track of the current line so runtime errors can say where they happened.
+fn write_block(
+    block: &[Spanned<Statement>],
+    data: &mut TranspilerData,
+    ins: &mut InstructionBlob,
+) -> Result<(), CompilerError> {
+    for statement in block {
+        if let Statement::Setting { .. } = statement.elem {
+            continue;
+        }
+
+        ins.instruction(Instruction::ldci4(statement.location.line as i32));
+        ins.instruction(Instruction::call("void [DM]DM.DmError::Line(int32)".to_owned()));
+        write_statement(&statement.elem, data, ins)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the value on the stack from the proc, leaving its call stack frame.
+fn write_return(ins: &mut InstructionBlob) {
+    ins.instruction(Instruction::call("void [DM]DM.DmError::Exit()".to_owned()));
+    ins.instruction(Instruction::ret);
+}
+
 fn write_statement(
     statement: &Statement,
     data: &mut TranspilerData,
@@ -216,8 +494,36 @@ fn write_statement(
             evaluate_expression(exp, data, ins)?;
             ins.instruction(Instruction::pop);
         }
-        Statement::Var(VarStatement { name, value, .. }) => {
-            let idx = data.add_local(name);
+        Statement::Var(VarStatement {
+            name,
+            value,
+            var_type,
+        }) if var_type.is_static => {
+            let declared_type = VariableType::from_type_path(&var_type.type_path);
+            let field = data.add_static_local(name, declared_type);
+            if let Some(initializer) = value {
+                // Only initialize the first time we get here.
+                let uniq = data.get_uniq();
+                let init_flag =
+                    data.add_meta_static_field(&format!("<>static_{}_{}_init", name, uniq), "bool");
+                let done_label = format!("si_{}", uniq);
+
+                ins.instruction(Instruction::ldsfld(init_flag.clone()));
+                ins.instruction(Instruction::brtrue(done_label.clone()));
+                ins.instruction(Instruction::ldci41);
+                ins.instruction(Instruction::stsfld(init_flag));
+                evaluate_expression(initializer, data, ins)?;
+                ins.instruction(Instruction::stsfld(field));
+                ins.label(done_label);
+                ins.instruction(Instruction::nop);
+            }
+        }
+        Statement::Var(VarStatement {
+            name,
+            value,
+            var_type,
+        }) => {
+            let idx = data.add_local(name, VariableType::from_type_path(&var_type.type_path));
             if let Some(initializer) = value {
                 evaluate_expression(initializer, data, ins)?;
                 ins.instruction(Instruction::stloc(idx));
@@ -252,9 +558,7 @@ fn write_statement(
                     }
                 }
 
-                for statement in statements {
-                    write_statement(&statement.elem, data, ins)?;
-                }
+                write_block(statements, data, ins)?;
 
                 ins.instruction(Instruction::br(end_label.clone()));
             }
@@ -263,9 +567,7 @@ fn write_statement(
                 ins.label(else_label);
                 ins.instruction(Instruction::nop);
 
-                for statement in statements {
-                    write_statement(&statement.elem, data, ins)?;
-                }
+                write_block(statements, data, ins)?;
             }
 
             ins.label(end_label);
@@ -286,10 +588,9 @@ fn write_statement(
             evaluate_truthy(ins);
             ins.instruction(Instruction::brfalse(exit_label.clone()));
 
-            for statement in statements {
-                write_statement(&statement.elem, data, ins)?;
-            }
+            write_block(statements, data, ins)?;
 
+            write_background_check(data, ins);
             ins.instruction(Instruction::br(test_label));
 
             ins.label(exit_label);
@@ -309,12 +610,11 @@ fn write_statement(
 
             ins.label(repeat_label.clone());
 
-            for statement in statements {
-                write_statement(&statement.elem, data, ins)?;
-            }
+            write_block(statements, data, ins)?;
 
             ins.label(test_label);
             ins.instruction(Instruction::nop);
+            write_background_check(data, ins);
             evaluate_expression(exp, data, ins)?;
             ins.instruction(Instruction::call(
                 "bool class [DM]DM.DmInternal::Truthy(object)".to_owned(),
@@ -340,14 +640,22 @@ fn write_statement(
                 return Err("Encountered break outside loop".into());
             }
         }
+        Statement::Del(expr) => {
+            evaluate_expression(expr, data, ins)?;
+            ins.instruction(Instruction::call("object [DM]DM.DmInternal::Del(object)".to_owned()));
+            ins.instruction(Instruction::pop);
+        }
+        Statement::Setting { .. } => {
+            // Read into the proc's settings when it was created.
+        }
         Statement::Return(None) => {
             // Default return value.
             ins.instruction(Instruction::ldloc0);
-            ins.instruction(Instruction::ret);
+            write_return(ins);
         }
         Statement::Return(Some(expr)) => {
             evaluate_expression(&expr, data, ins)?;
-            ins.instruction(Instruction::ret);
+            write_return(ins);
         }
         _ => {
             return Err(format!("unknown statement: {:?}", statement).into());
@@ -369,54 +677,45 @@ fn evaluate_expression(
             term,
             follow,
         } => {
-            let mut term_blob = InstructionBlob::default();
-            let mut term_type = evaluate_term(&term.elem, data, &mut term_blob)?;
-            for follow in follow.iter().rev() {
-                let old_blob = term_blob;
-                term_blob = InstructionBlob::default();
-                term_type =
-                    evaluate_follow(&follow.elem, term_type, old_blob, data, &mut term_blob)?;
-            }
+            let term_type = evaluate_base(term, follow, data, ins)?;
 
             for _unary in unary.iter().rev() {
                 // TODO: Unary ops.
             }
 
-            ins.absord(term_blob);
             return Ok(term_type);
         }
         Expression::BinaryOp { op, lhs, rhs } => {
             match op {
-                BinaryOp::Add
-                | BinaryOp::Mul
-                | BinaryOp::Sub
-                | BinaryOp::Div
-                | BinaryOp::Eq
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
+                    // Joining text needs DM's rules for turning things into text,
+                    // and bad operands are a DM runtime error rather than a binder exception.
+                    let method = match op {
+                        BinaryOp::Add => "Add",
+                        BinaryOp::Sub => "Sub",
+                        BinaryOp::Mul => "Mul",
+                        BinaryOp::Div => "Div",
+                        _ => "Mod",
+                    };
+                    evaluate_expression(lhs, data, ins)?;
+                    evaluate_expression(rhs, data, ins)?;
+                    ins.instruction(Instruction::call(format!(
+                        "object [DM]DM.DmInternal::{}(object, object)",
+                        method
+                    )));
+                }
+                BinaryOp::Eq
                 | BinaryOp::NotEq
                 | BinaryOp::Greater
                 | BinaryOp::GreaterEq
                 | BinaryOp::Less
-                | BinaryOp::LessEq
-                | BinaryOp::Mod => {
+                | BinaryOp::LessEq => {
                     let mut arg_blob = InstructionBlob::default();
                     evaluate_expression(lhs, data, &mut arg_blob)?;
                     evaluate_expression(rhs, data, &mut arg_blob)?;
                     do_dynamic_invoke(DynamicInvokeType::BinaryOp(*op), arg_blob, data, ins);
-
-                    match op {
-                        BinaryOp::Eq
-                        | BinaryOp::NotEq
-                        | BinaryOp::Greater
-                        | BinaryOp::GreaterEq
-                        | BinaryOp::Less
-                        | BinaryOp::LessEq => {
-                            ins.instruction(Instruction::unboxany(
-                                "[mscorlib]System.Boolean".to_owned(),
-                            ));
-                            bool_to_float(data, ins)
-                        }
-                        _ => {}
-                    };
+                    ins.instruction(Instruction::unboxany("[mscorlib]System.Boolean".to_owned()));
+                    bool_to_float(data, ins)
                 }
                 BinaryOp::And => {
                     let uniq = data.get_uniq();
@@ -445,15 +744,46 @@ fn evaluate_expression(
                     ins.instruction(Instruction::nop);
                 }
                 BinaryOp::LShift => {
-                    let mut arg_blob = InstructionBlob::default();
-                    evaluate_expression(lhs, data, &mut arg_blob)?;
-                    evaluate_expression(rhs, data, &mut arg_blob)?;
-                    let invoke = DynamicInvokeType::MemberInvoke {
-                        arg_count: 1,
-                        expect_return: true,
-                        method_name: "output".to_owned(),
-                    };
-                    do_dynamic_invoke(invoke, arg_blob, data, ins);
+                    // S["key"] << value writes to a savefile, so it can't just read S["key"] first.
+                    if let Some((term, rest, index)) = split_index(lhs) {
+                        evaluate_base(term, rest, data, ins)?;
+                        evaluate_expression(index, data, ins)?;
+                        evaluate_expression(rhs, data, ins)?;
+                        ins.instruction(Instruction::call(
+                            "object [DM]DM.DmInternal::OutputIndex(object, object, object)"
+                                .to_owned(),
+                        ));
+                    } else {
+                        evaluate_expression(lhs, data, ins)?;
+                        evaluate_expression(rhs, data, ins)?;
+                        ins.instruction(Instruction::call(
+                            "object [DM]DM.DmInternal::Output(object, object)".to_owned(),
+                        ));
+                    }
+                }
+                BinaryOp::RShift => {
+                    // F >> V reads from a file or savefile into V.
+                    write_assign_value(
+                        rhs,
+                        &mut |data, ins| {
+                            if let Some((term, rest, index)) = split_index(lhs) {
+                                evaluate_base(term, rest, data, ins)?;
+                                evaluate_expression(index, data, ins)?;
+                                ins.instruction(Instruction::call(
+                                    "object [DM]DM.DmInternal::InputIndex(object, object)"
+                                        .to_owned(),
+                                ));
+                            } else {
+                                evaluate_expression(lhs, data, ins)?;
+                                ins.instruction(Instruction::call(
+                                    "object [DM]DM.DmInternal::Input(object)".to_owned(),
+                                ));
+                            }
+                            Ok(())
+                        },
+                        data,
+                        ins,
+                    )?;
                 }
                 _ => {
                     return Err(format!("Unknown op: {:?}", op).into());
@@ -465,25 +795,7 @@ fn evaluate_expression(
             lhs,
             rhs,
         } => {
-            if let Expression::Base {
-                term:
-                    Spanned {
-                        elem: Term::Ident(varname),
-                        ..
-                    },
-                ..
-            } = *lhs.clone()
-            {
-                if let Some(idx) = data.get_local(&varname) {
-                    evaluate_expression(rhs, data, ins)?;
-                    ins.instruction(Instruction::dup);
-                    ins.instruction(Instruction::stloc(idx));
-                } else {
-                    return Err(format!("Unknown variable: {}", &varname).into());
-                }
-            } else {
-                return Err("That lvalue is too complex for me.".into());
-            }
+            write_assign(lhs, rhs, data, ins)?;
         }
         _ => {
             return Err(format!("Unable to handle expression type: {:?}", expression).into());
@@ -493,6 +805,167 @@ fn evaluate_expression(
     Ok(VariableType::Unspecified)
 }
 
+/// Evaluates a term and the follows (field accesses, calls, indexing) after it.
+fn evaluate_base(
+    term: &Spanned<Term>,
+    follow: &[Spanned<Follow>],
+    data: &mut TranspilerData,
+    ins: &mut InstructionBlob,
+) -> Result<VariableType, CompilerError> {
+    let mut term_blob = InstructionBlob::default();
+    let mut term_type = evaluate_term(&term.elem, data, &mut term_blob)?;
+    for follow in follow {
+        let old_blob = term_blob;
+        term_blob = InstructionBlob::default();
+        term_type = evaluate_follow(&follow.elem, term_type, old_blob, data, &mut term_blob)?;
+    }
+
+    ins.absord(term_blob);
+    Ok(term_type)
+}
+
+/// Splits `A.B[index]` into the term, the follows before the index, and the index.
+fn split_index(expr: &Expression) -> Option<(&Spanned<Term>, &[Spanned<Follow>], &Expression)> {
+    match expr {
+        Expression::Base {
+            unary,
+            term,
+            follow,
+        } if unary.len() == 0 => match follow.split_last() {
+            Some((
+                Spanned {
+                    elem: Follow::Index(index),
+                    ..
+                },
+                rest,
+            )) => {
+                let term: &Spanned<Term> = term;
+                let index: &Expression = index;
+                Some((term, rest, index))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Compiles `lhs = rhs`, leaving the assigned value on the stack.
+fn write_assign(
+    lhs: &Expression,
+    rhs: &Expression,
+    data: &mut TranspilerData,
+    ins: &mut InstructionBlob,
+) -> Result<(), CompilerError> {
+    write_assign_value(
+        lhs,
+        &mut |data, ins| evaluate_expression(rhs, data, ins).map(|_| ()),
+        data,
+        ins,
+    )
+}
+
+/// Writes the value of an assignment onto the stack.
+type ValueWriter<'v> =
+    dyn FnMut(&mut TranspilerData, &mut InstructionBlob) -> Result<(), CompilerError> + 'v;
+
+/// Compiles an assignment to `lhs` of the value `rhs` writes, leaving the value on the stack.
+fn write_assign_value(
+    lhs: &Expression,
+    rhs: &mut ValueWriter,
+    data: &mut TranspilerData,
+    ins: &mut InstructionBlob,
+) -> Result<(), CompilerError> {
+    let (term, follow) = match lhs {
+        Expression::Base {
+            unary,
+            term,
+            follow,
+        } if unary.len() == 0 => (term, follow),
+        _ => return Err("That lvalue is too complex for me.".into()),
+    };
+
+    let (last, rest) = match follow.split_last() {
+        Some(split) => split,
+        None => {
+            if let Term::Ident(varname) = &term.elem {
+                return write_assign_ident(varname, rhs, data, ins);
+            }
+            return Err("That lvalue is too complex for me.".into());
+        }
+    };
+
+    // Everything but the last follow is what we're assigning into.
+    let mut owner_blob = InstructionBlob::default();
+    let owner_type = evaluate_base(term, rest, data, &mut owner_blob)?;
+
+    match &last.elem {
+        Follow::Field(_, field_name) => {
+            if let VariableType::Object(path) = &owner_type {
+                if let Some(var_field) = get_var_field(data.compiler_state, path, field_name) {
+                    ins.absord(owner_blob);
+                    rhs(data, ins)?;
+                    var_field.write_store(data, ins);
+                    return Ok(());
+                }
+
+                return Err(format!("Unknown var {} on {}", field_name, path).into());
+            }
+
+            rhs(data, &mut owner_blob)?;
+            do_dynamic_invoke(
+                DynamicInvokeType::SetMember(field_name.clone()),
+                owner_blob,
+                data,
+                ins,
+            );
+        }
+        Follow::Index(index) => {
+            ins.absord(owner_blob);
+            evaluate_expression(index, data, ins)?;
+            rhs(data, ins)?;
+            ins.instruction(Instruction::call(
+                "object [DM]DM.DmList::SetIndex(object, object, object)".to_owned(),
+            ));
+        }
+        _ => return Err("Can't assign to a proc call.".into()),
+    }
+
+    Ok(())
+}
+
+/// Compiles `varname = rhs` for a local, var on src, or global.
+fn write_assign_ident(
+    varname: &str,
+    rhs: &mut ValueWriter,
+    data: &mut TranspilerData,
+    ins: &mut InstructionBlob,
+) -> Result<(), CompilerError> {
+    if let Some(idx) = data.get_local(varname) {
+        rhs(data, ins)?;
+        ins.instruction(Instruction::dup);
+        ins.instruction(Instruction::stloc(idx));
+    } else if let Some(field) = data.get_static_local(varname).map(|f| f.to_owned()) {
+        rhs(data, ins)?;
+        ins.instruction(Instruction::dup);
+        ins.instruction(Instruction::stsfld(field));
+    } else if let Some(var_field) = get_src_var_field(data, varname) {
+        ins.instruction(Instruction::ldarg0);
+        rhs(data, ins)?;
+        var_field.write_store(data, ins);
+    } else if let Some(global) = data.compiler_state.global_vars.get(varname) {
+        if global.mutability != VariableMutability::Normal {
+            return Err(format!("Cannot assign to constant global: {}", varname).into());
+        }
+        rhs(data, ins)?;
+        ins.instruction(Instruction::dup);
+        ins.instruction(Instruction::stsfld(format!("object byond_root::{}", varname)));
+    } else {
+        return Err(format!("Unknown variable: {}", varname).into());
+    }
+
+    Ok(())
+}
+
 fn evaluate_term(
     term: &Term,
     data: &mut TranspilerData,
@@ -518,21 +991,53 @@ fn evaluate_term(
             if ident == "src" {
                 ins.instruction(Instruction::ldarg0);
                 Ok(VariableType::Unspecified)
+            } else if (ident == "type" || ident == "parent_type") && !data.is_static {
+                ins.instruction(Instruction::ldarg0);
+                load_type_var(ident, ins);
+                Ok(VariableType::Unspecified)
+            } else if ident == "usr" {
+                // Nobody is playing, so there is no usr.
+                ins.instruction(Instruction::ldnull);
+                Ok(VariableType::Unspecified)
             } else if ident == "." {
                 ins.instruction(Instruction::ldloc0);
                 Ok(VariableType::Unspecified)
+            } else if ident == "vars" && !data.is_static {
+                ins.instruction(Instruction::ldarg0);
+                ins.instruction(Instruction::call(
+                    "class [DM]DM.DmList [DM]DM.DmVars::VarsOf(object)".to_owned(),
+                ));
+                Ok(VariableType::Unspecified)
+            } else if let (true, Some(args_local)) = (ident == "args", data.args_local) {
+                // The list only gets made the first time it's used.
+                let done_label = format!("args_{}", data.get_uniq());
+                ins.instruction(Instruction::ldloc(args_local));
+                ins.instruction(Instruction::dup);
+                ins.instruction(Instruction::brtrue(done_label.clone()));
+                ins.instruction(Instruction::pop);
+                ins.instruction(Instruction::ldarg(data.args_arg()));
+                ins.instruction(Instruction::call(
+                    "class [DM]DM.DmList [DM]DM.DmList::FromArgs(object[])".to_owned(),
+                ));
+                ins.instruction(Instruction::dup);
+                ins.instruction(Instruction::stloc(args_local));
+                ins.label(done_label);
+                ins.instruction(Instruction::nop);
+                Ok(VariableType::Unspecified)
             } else if let Some(idx) = data.get_local(ident) {
                 ins.instruction(Instruction::ldloc(idx));
                 Ok(VariableType::Unspecified)
+            } else if let Some(field) = data.get_static_local(ident) {
+                ins.instruction(Instruction::ldsfld(field.to_owned()));
+                Ok(VariableType::Unspecified)
+            } else if let Some(var_field) = get_src_var_field(data, ident) {
+                ins.instruction(Instruction::ldarg0);
+                var_field.write_load(ins);
+                Ok(var_field.var_type)
             } else if data.compiler_state.global_vars.contains_key(ident) {
                 let global = data.compiler_state.global_vars.get(ident).unwrap();
                 ins.instruction(Instruction::ldsfld(format!("object byond_root::{}", ident)));
-                match &global.var_type {
-                    VariableType::Object(path) => {
-                        ins.instruction(Instruction::castclass(byond_path_to_class(path)));
-                    }
-                    VariableType::Unspecified => {}
-                };
+                cast_to_type(&global.var_type, ins);
                 Ok(global.var_type.clone())
             } else {
                 Err(format!("Unknown identifier: {}", &ident).into())
@@ -542,38 +1047,290 @@ fn evaluate_term(
             ins.instruction(Instruction::ldstr(val.to_owned()));
             Ok(VariableType::Unspecified)
         }
+        Term::InterpString(first, parts) => {
+            ins.instruction(Instruction::ldstr(first.clone()));
+            for (expr, text) in parts {
+                if let Some(expr) = expr {
+                    evaluate_expression(expr, data, ins)?;
+                    write_stringify(ins);
+                    write_string_concat(ins);
+                }
+                if text.len() != 0 {
+                    ins.instruction(Instruction::ldstr(text.clone()));
+                    write_string_concat(ins);
+                }
+            }
+            Ok(VariableType::Unspecified)
+        }
         Term::Expr(expr) => evaluate_expression(expr, data, ins),
+        Term::Prefab(prefab) => {
+            let path = prefab_to_path(prefab, data)?;
+            load_type_path(&path, ins);
+            Ok(VariableType::Unspecified)
+        }
+        Term::NewPrefab { prefab, args } => {
+            let path = prefab_to_path(prefab, data)?;
+            let class_name = byond_path_to_class(&path);
+            ins.instruction(Instruction::newobj(format!(
+                "instance void {}::.ctor()",
+                class_name
+            )));
+
+            let compiler_state = data.compiler_state;
+            if let Some(new_proc) = compiler_state.get_type_proc(&path, "New") {
+                // Builtin procs we don't implement are static stubs, there's nothing to call.
+                let implemented = match &new_proc.source {
+                    ProcSource::Std(StdProc::Unimplemented(_)) => false,
+                    _ => true,
+                };
+                if implemented {
+                    let args = args.as_ref().map(|a| a.as_slice()).unwrap_or(&[]);
+                    ins.instruction(Instruction::dup);
+                    let signature = write_proc_args(new_proc, args, data, ins)?;
+
+                    let owner = new_proc.owner.as_ref().unwrap();
+                    ins.instruction(Instruction::callvirt(format!(
+                        "instance object {}::New({})",
+                        byond_path_to_class(owner),
+                        signature
+                    )));
+                    ins.instruction(Instruction::pop);
+                }
+            }
+
+            Ok(VariableType::Object(path))
+        }
+        Term::NewMiniExpr { expr, args } => {
+            // new T(), where T holds a type path at runtime.
+            if expr.fields.len() != 0 {
+                return Err("new with a field access is not implemented yet.".into());
+            }
+            evaluate_term(&Term::Ident(expr.ident.clone()), data, ins)?;
+            let args = args.as_ref().map(|a| a.as_slice()).unwrap_or(&[]);
+            write_args_array(args, data, ins)?;
+            ins.instruction(Instruction::call(
+                "object [DM]DM.DmPath::New(object, object[])".to_owned(),
+            ));
+            Ok(VariableType::Unspecified)
+        }
         Term::Call(name, args) => {
-            if !data.is_static {
-                return Err("Unscoped non-static calls are not implemented yet.".into());
+            if let Some(var_type) = write_intrinsic_call(name, args, data, ins)? {
+                return Ok(var_type);
             }
-            let tree = data.state.get_tree();
-            let root = tree.root();
-            if let Some(proc) = root.get_proc(name) {
-                let mut args_tok = String::new();
-                if proc.parameters.len() != 0 {
-                    args_tok.push_str("object");
-                    for _ in 1..proc.parameters.len() {
-                        args_tok.push_str(", object");
-                    }
-                }
-                //println!("{:?}", proc);
-                for expr in args {
-                    evaluate_expression(expr, data, ins)?;
-                }
+
+            let compiler_state = data.compiler_state;
+            if let Some(global_proc) = compiler_state.global_procs.get(name) {
+                let signature = write_proc_args(global_proc, args, data, ins)?;
                 ins.instruction(Instruction::call(format!(
                     "object byond_root::{}({})",
-                    name, args_tok
+                    name, signature
                 )));
                 Ok(VariableType::Unspecified)
             } else {
-                panic!(format!("Method does not exist: {}", name));
+                Err(format!("Method does not exist: {}", name).into())
+            }
+        }
+        Term::Pick(choices) => {
+            // pick(prob(P); A, B): weights are optional, and usually written with prob().
+            ins.instruction(Instruction::ldci4(choices.len() as i32));
+            ins.instruction(Instruction::newarr("[mscorlib]System.Object".to_owned()));
+            for (i, (_, value)) in choices.iter().enumerate() {
+                ins.instruction(Instruction::dup);
+                ins.instruction(Instruction::ldci4(i as i32));
+                evaluate_expression(value, data, ins)?;
+                ins.instruction(Instruction::stelemref);
+            }
+
+            ins.instruction(Instruction::ldci4(choices.len() as i32));
+            ins.instruction(Instruction::newarr("[mscorlib]System.Object".to_owned()));
+            for (i, (weight, _)) in choices.iter().enumerate() {
+                let weight = match weight {
+                    Some(weight) => weight,
+                    None => continue,
+                };
+                let weight = match expression_as_term(weight) {
+                    Some(Term::Call(name, args)) if name == "prob" && args.len() == 1 => &args[0],
+                    _ => weight,
+                };
+
+                ins.instruction(Instruction::dup);
+                ins.instruction(Instruction::ldci4(i as i32));
+                evaluate_expression(weight, data, ins)?;
+                ins.instruction(Instruction::stelemref);
             }
+
+            ins.instruction(Instruction::call(
+                "object [DM]DM.DmRandom::PickWeighted(object[], object[])".to_owned(),
+            ));
+            Ok(VariableType::Unspecified)
+        }
+        Term::List(args) => {
+            ins.instruction(Instruction::newobj(
+                "instance void [DM]DM.DmList::.ctor()".to_owned(),
+            ));
+            for arg in args {
+                ins.instruction(Instruction::dup);
+                match arg {
+                    Expression::AssignOp {
+                        op: AssignOp::Assign,
+                        lhs,
+                        rhs,
+                    } => {
+                        // list(a = 1) uses "a" as the key, not the variable a.
+                        if let Some(Term::Ident(key)) = expression_as_term(lhs) {
+                            ins.instruction(Instruction::ldstr(key.clone()));
+                        } else {
+                            evaluate_expression(lhs, data, ins)?;
+                        }
+                        evaluate_expression(rhs, data, ins)?;
+                        ins.instruction(Instruction::callvirt(
+                            "instance void [DM]DM.DmList::SetAssoc(object, object)".to_owned(),
+                        ));
+                    }
+                    _ => {
+                        evaluate_expression(arg, data, ins)?;
+                        ins.instruction(Instruction::callvirt(
+                            "instance void [DM]DM.DmList::Append(object)".to_owned(),
+                        ));
+                    }
+                }
+            }
+            Ok(VariableType::Unspecified)
+        }
+        Term::Resource(path) => {
+            ins.instruction(Instruction::ldstr(path.clone()));
+            ins.instruction(Instruction::newobj(
+                "instance void [DM]DM.DmFile::.ctor(string)".to_owned(),
+            ));
+            Ok(VariableType::Unspecified)
+        }
+        Term::Input {
+            args,
+            input_type,
+            in_list,
+        } => {
+            // The runtime sorts out whether the first argument is usr.
+            write_args_array(args, data, ins)?;
+            // Like "text|null", empty without an as clause.
+            ins.instruction(Instruction::ldstr(input_type.to_string()));
+            match in_list {
+                Some(list) => {
+                    evaluate_expression(list, data, ins)?;
+                }
+                None => ins.instruction(Instruction::ldnull),
+            }
+            ins.instruction(Instruction::call(
+                "object [DM]DM.DmConsole::Input(object[], string, object)".to_owned(),
+            ));
+            Ok(VariableType::Unspecified)
         }
         t => Err(format!("Unable to handle term: {:?}", t).into()),
     }
 }
 
+/// Compiles calls to procs that are better written inline than as an actual call.
+/// Returns `None` if the call should be compiled as a normal call instead.
+fn write_intrinsic_call(
+    name: &str,
+    args: &[Expression],
+    data: &mut TranspilerData,
+    ins: &mut InstructionBlob,
+) -> Result<Option<VariableType>, CompilerError> {
+    match (name, args.len()) {
+        ("istype", 1) => {
+            // istype(x) checks against the declared type of x.
+            let var_type = match expression_as_term(&args[0]) {
+                Some(Term::Ident(ident)) => match data.get_local_type(ident) {
+                    Some(var_type) => Some(var_type.clone()),
+                    None => data
+                        .compiler_state
+                        .global_vars
+                        .get(ident)
+                        .map(|global| global.var_type.clone()),
+                },
+                _ => None,
+            };
+
+            match var_type {
+                Some(VariableType::Object(path)) => {
+                    evaluate_expression(&args[0], data, ins)?;
+                    write_isinst(&path, ins);
+                    Ok(Some(VariableType::Unspecified))
+                }
+                _ => Err("istype() with one argument needs a variable with a declared type.".into()),
+            }
+        }
+        ("istype", 2) => {
+            if let Some(Term::Prefab(prefab)) = expression_as_term(&args[1]) {
+                let path = prefab_to_path(prefab, data)?;
+                evaluate_expression(&args[0], data, ins)?;
+                write_isinst(&path, ins);
+                return Ok(Some(VariableType::Unspecified));
+            }
+
+            // Type isn't known at compile time, let the runtime figure it out.
+            Ok(None)
+        }
+        ("initial", 1) | ("issaved", 1) => {
+            // These take a var, not its value.
+            // So load whatever owns the var, and pass the name along.
+            let var_name = match &args[0] {
+                Expression::Base {
+                    unary,
+                    term,
+                    follow,
+                } if unary.len() == 0 => match follow.split_last() {
+                    Some((last, rest)) => match &last.elem {
+                        Follow::Field(_, var_name) => {
+                            evaluate_base(term, rest, data, ins)?;
+                            var_name.clone()
+                        }
+                        _ => return Err(format!("{}() needs a var.", name).into()),
+                    },
+                    None => match &term.elem {
+                        Term::Ident(var_name) if get_src_var_field(data, var_name).is_some() => {
+                            ins.instruction(Instruction::ldarg0);
+                            var_name.clone()
+                        }
+                        _ => return Err(format!("{}() needs a var.", name).into()),
+                    },
+                },
+                _ => return Err(format!("{}() needs a var.", name).into()),
+            };
+
+            ins.instruction(Instruction::ldstr(var_name));
+            ins.instruction(Instruction::call(format!(
+                "object [DM]DM.DmVars::{}(object, string)",
+                if name == "initial" { "Initial" } else { "IsSaved" }
+            )));
+            Ok(Some(VariableType::Unspecified))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Replaces the object on top of the stack with whether it's an instance of the type, as a DM number.
+fn write_isinst(path: &ByondPath, ins: &mut InstructionBlob) {
+    ins.instruction(Instruction::isinst(byond_path_to_class(path)));
+    ins.instruction(Instruction::ldnull);
+    ins.instruction(Instruction::cgtun);
+    ins.instruction(Instruction::call(
+        "object [DM]DM.DmInternal::Bool(bool)".to_owned(),
+    ));
+}
+
+/// Gets the term of an expression that is just a term, without any operators or follows.
+pub(crate) fn expression_as_term(expression: &Expression) -> Option<&Term> {
+    match expression {
+        Expression::Base {
+            unary,
+            term,
+            follow,
+        } if unary.len() == 0 && follow.len() == 0 => Some(&term.elem),
+        _ => None,
+    }
+}
+
 fn evaluate_follow(
     follow: &Follow,
     term_type: VariableType,
@@ -582,58 +1339,95 @@ fn evaluate_follow(
     ins: &mut InstructionBlob,
 ) -> Result<VariableType, CompilerError> {
     match follow {
-        Follow::Call(_, method_name, args) => {
-            for arg in args {
-                evaluate_expression(arg, data, &mut term_blob)?;
-            }
-
-            match term_type {
-                VariableType::Unspecified => {
-                    do_dynamic_invoke(
-                        DynamicInvokeType::MemberInvoke {
-                            arg_count: args.len() as u16,
-                            expect_return: true,
-                            method_name: method_name.clone(),
-                        },
-                        term_blob,
-                        data,
-                        ins,
-                    );
-                    Ok(VariableType::Unspecified)
+        Follow::Call(_, method_name, args) => match term_type {
+            VariableType::Unspecified => {
+                for arg in args {
+                    evaluate_expression(arg, data, &mut term_blob)?;
                 }
-                VariableType::Object(path) => {
-                    if !data.compiler_state.types.contains_key(&path) {
-                        return Err("Unable to find type.".into());
-                    }
-                    let type_instance = &data.compiler_state.types[&path];
-                    if !type_instance.procs.contains_key(method_name) {
-                        return Err("Unable to find proc.".into());
-                    }
 
-                    //let instance_proc = &type_instance.procs[method_name];
-                    // oh shit we got it.
-                    ins.absord(term_blob);
-                    let arg_count = args.len();
-                    let mut args = String::new();
-                    if arg_count > 0 {
-                        args.push_str("object");
-
-                        if arg_count > 1 {
-                            for _ in 1..arg_count {
-                                args.push_str(", object");
-                            }
-                        }
+                do_dynamic_invoke(
+                    DynamicInvokeType::MemberInvoke {
+                        arg_count: args.len() as u16,
+                        expect_return: true,
+                        method_name: method_name.clone(),
+                    },
+                    term_blob,
+                    data,
+                    ins,
+                );
+                Ok(VariableType::Unspecified)
+            }
+            VariableType::Object(path) => {
+                let compiler_state = data.compiler_state;
+                let type_proc = match compiler_state.get_type_proc(&path, method_name) {
+                    Some(type_proc) => type_proc,
+                    None => {
+                        return Err(
+                            format!("Unable to find proc {} on {}", method_name, path).into()
+                        )
                     }
-                    ins.instruction(Instruction::call(format!(
-                        "instance object byond_root{}::{}({})",
-                        path, method_name, args
-                    )));
-
-                    Ok(VariableType::Unspecified)
+                };
+                let owner = type_proc.owner.as_ref().unwrap();
+
+                ins.absord(term_blob);
+                let signature = write_proc_args(type_proc, args, data, ins)?;
+                ins.instruction(Instruction::callvirt(format!(
+                    "instance object {}::{}({})",
+                    byond_path_to_class(owner),
+                    method_name,
+                    signature
+                )));
+                Ok(VariableType::Unspecified)
+            }
+        },
+        Follow::Field(_, field_name) if field_name == "type" || field_name == "parent_type" => {
+            ins.absord(term_blob);
+            load_type_var(field_name, ins);
+            Ok(VariableType::Unspecified)
+        }
+        Follow::Field(_, field_name) if field_name == "vars" => {
+            ins.absord(term_blob);
+            ins.instruction(Instruction::call(
+                "class [DM]DM.DmList [DM]DM.DmVars::VarsOf(object)".to_owned(),
+            ));
+            Ok(VariableType::Unspecified)
+        }
+        Follow::Field(_, field_name) if field_name == "len" && may_be_list(&term_type) => {
+            // DmList has no len member for the binder to find.
+            ins.absord(term_blob);
+            ins.instruction(Instruction::call(
+                "object [DM]DM.DmList::Len(object)".to_owned(),
+            ));
+            Ok(VariableType::Unspecified)
+        }
+        Follow::Field(_, field_name) => match term_type {
+            VariableType::Unspecified => {
+                do_dynamic_invoke(
+                    DynamicInvokeType::GetMember(field_name.clone()),
+                    term_blob,
+                    data,
+                    ins,
+                );
+                Ok(VariableType::Unspecified)
+            }
+            VariableType::Object(path) => {
+                if let Some(var_field) = get_var_field(data.compiler_state, &path, field_name) {
+                    ins.absord(term_blob);
+                    var_field.write_load(ins);
+                    Ok(var_field.var_type)
+                } else {
+                    Err(format!("Unknown var {} on {}", field_name, path).into())
                 }
             }
+        },
+        Follow::Index(index) => {
+            evaluate_expression(index, data, &mut term_blob)?;
+            ins.absord(term_blob);
+            ins.instruction(Instruction::call(
+                "object [DM]DM.DmList::Index(object, object)".to_owned(),
+            ));
+            Ok(VariableType::Unspecified)
         }
-        a => Err(format!("Non-call follows not implemented: {:?}", a).into()),
     }
 }
 
@@ -682,9 +1476,12 @@ fn do_dynamic_invoke(
                         if *expect_return { "Func" } else { "Action" },
                         type_args_count, type_args), *arg_count)
             },
-            DynamicInvokeType::BinaryOp(_) => {
+            DynamicInvokeType::BinaryOp(_) | DynamicInvokeType::SetMember(_) => {
                 ("class [mscorlib]System.Func`4<class [System.Core]System.Runtime.CompilerServices.CallSite, object, object, object>".to_owned(), 1)
             }
+            DynamicInvokeType::GetMember(_) => {
+                ("class [mscorlib]System.Func`3<class [System.Core]System.Runtime.CompilerServices.CallSite, object, object>".to_owned(), 0)
+            }
         };
         // Callsite`1<call_type> type, because it's used a lot.
         let call_site_calltype = format!(
@@ -755,6 +1552,9 @@ fn do_dynamic_invoke(
                 _ => panic!("Unsupported binary op!"),
             }));
         }
+        DynamicInvokeType::GetMember(ref name) | DynamicInvokeType::SetMember(ref name) => {
+            ins.instruction(Instruction::ldstr(name.clone()));
+        }
         /*
         ref a => {
             println!("{:?}", a);
@@ -793,6 +1593,12 @@ fn do_dynamic_invoke(
         DynamicInvokeType::BinaryOp(_) => {
             ins.instruction(Instruction::call("class [System.Core]System.Runtime.CompilerServices.CallSiteBinder [Microsoft.CSharp]Microsoft.CSharp.RuntimeBinder.Binder::BinaryOperation(valuetype [Microsoft.CSharp]Microsoft.CSharp.RuntimeBinder.CSharpBinderFlags, valuetype [System.Core]System.Linq.Expressions.ExpressionType, class [mscorlib]System.Type, class [mscorlib]System.Collections.Generic.IEnumerable`1<class [Microsoft.CSharp]Microsoft.CSharp.RuntimeBinder.CSharpArgumentInfo>)".to_owned()))
         }
+        DynamicInvokeType::GetMember(_) => {
+            ins.instruction(Instruction::call("class [System.Core]System.Runtime.CompilerServices.CallSiteBinder [Microsoft.CSharp]Microsoft.CSharp.RuntimeBinder.Binder::GetMember(valuetype [Microsoft.CSharp]Microsoft.CSharp.RuntimeBinder.CSharpBinderFlags, string, class [mscorlib]System.Type, class [mscorlib]System.Collections.Generic.IEnumerable`1<class [Microsoft.CSharp]Microsoft.CSharp.RuntimeBinder.CSharpArgumentInfo>)".to_owned()))
+        }
+        DynamicInvokeType::SetMember(_) => {
+            ins.instruction(Instruction::call("class [System.Core]System.Runtime.CompilerServices.CallSiteBinder [Microsoft.CSharp]Microsoft.CSharp.RuntimeBinder.Binder::SetMember(valuetype [Microsoft.CSharp]Microsoft.CSharp.RuntimeBinder.CSharpBinderFlags, string, class [mscorlib]System.Type, class [mscorlib]System.Collections.Generic.IEnumerable`1<class [Microsoft.CSharp]Microsoft.CSharp.RuntimeBinder.CSharpArgumentInfo>)".to_owned()))
+        }
     };
 
     // Create call site and assign it to the meta field.
@@ -832,12 +1638,18 @@ fn do_dynamic_invoke(
                 ret_type, call_type, invoke_args
             )));
         }
-        DynamicInvokeType::BinaryOp(_) => {
+        DynamicInvokeType::BinaryOp(_) | DynamicInvokeType::SetMember(_) => {
             ins.instruction(Instruction::callvirt(format!(
                 "instance !3 {}::Invoke(!0, !1, !2)",
                 call_type
             )));
         }
+        DynamicInvokeType::GetMember(_) => {
+            ins.instruction(Instruction::callvirt(format!(
+                "instance !2 {}::Invoke(!0, !1)",
+                call_type
+            )));
+        }
     }
 }
 
@@ -849,13 +1661,19 @@ enum DynamicInvokeType {
         method_name: String,
     },
     BinaryOp(BinaryOp),
+    GetMember(String),
+    SetMember(String),
 }
 
 fn get_proc_body_details<'a>(the_proc: &Proc, state: &'a DMState) -> Option<&'a Block> {
-    let root = state.get_tree().root().get();
-    let proc = &root.procs[&the_proc.name];
+    let tree = state.get_tree();
+    let owner = match &the_proc.owner {
+        Some(path) => tree.find(&path.to_string())?,
+        None => tree.root(),
+    };
+    let proc = owner.get().procs.get(&the_proc.name)?;
 
-    let code = &proc.value[0].code;
+    let code = &proc.value.last()?.code;
 
     match code {
         Code::Present(block) => Some(block),
@@ -863,6 +1681,15 @@ fn get_proc_body_details<'a>(the_proc: &Proc, state: &'a DMState) -> Option<&'a
     }
 }
 
+/// In background procs, lets other procs run if this one has been running for too long.
+fn write_background_check(data: &TranspilerData, ins: &mut InstructionBlob) {
+    if data.is_background {
+        ins.instruction(Instruction::call(
+            "void [DM]DM.DmScheduler::Background()".to_owned(),
+        ));
+    }
+}
+
 fn evaluate_truthy(ins: &mut InstructionBlob) {
     ins.instruction(Instruction::call(
         "bool class [DM]DM.DmInternal::Truthy(object)".to_owned(),
@@ -890,3 +1717,191 @@ pub fn byond_path_to_class(path: &ByondPath) -> String {
 
     format!("byond_root{}", path)
 }
+
+/// Resolves the path of a prefab such as `/obj/item` into a known type.
+fn prefab_to_path(prefab: &Prefab, data: &TranspilerData) -> Result<ByondPath, CompilerError> {
+    if prefab.vars.len() != 0 {
+        return Err("Prefabs with var overrides are not implemented yet.".into());
+    }
+
+    let mut segments = vec![];
+    for (op, segment) in &prefab.path {
+        if *op != PathOp::Slash {
+            return Err(format!("Relative type paths are not implemented yet: {:?}", prefab).into());
+        }
+        segments.push(segment.as_str());
+    }
+
+    let path = ByondPath::new(&segments, true);
+    if !data.compiler_state.types.contains_key(&path) {
+        return Err(format!("Unknown type path: {}", path).into());
+    }
+
+    Ok(path)
+}
+
+/// Loads the runtime type path object for a type onto the stack.
+pub fn load_type_path(path: &ByondPath, ins: &mut InstructionBlob) {
+    ins.instruction(Instruction::ldtoken(byond_path_to_class(path)));
+    ins.instruction(Instruction::ldstr(path.to_string()));
+    ins.instruction(Instruction::call("class [DM]DM.DmPath [DM]DM.DmPath::Get(valuetype [mscorlib]System.RuntimeTypeHandle, string)".to_owned()));
+}
+
+/// Replaces the object on top of the stack with its `type` or `parent_type`.
+fn load_type_var(var_name: &str, ins: &mut InstructionBlob) {
+    ins.instruction(Instruction::call(match var_name {
+        "type" => "class [DM]DM.DmPath [DM]DM.DmPath::Of(object)".to_owned(),
+        _ => "class [DM]DM.DmPath [DM]DM.DmPath::ParentOf(object)".to_owned(),
+    }));
+}
+
+/// Turns the value on the stack into a string, the way DM prints it.
+pub(crate) fn write_stringify(ins: &mut InstructionBlob) {
+    ins.instruction(Instruction::call("string [DM]DM.DmText::Stringify(object)".to_owned()));
+}
+
+/// Joins the two strings on the stack.
+fn write_string_concat(ins: &mut InstructionBlob) {
+    ins.instruction(Instruction::call(
+        "string [mscorlib]System.String::Concat(string, string)".to_owned(),
+    ));
+}
+
+/// Evaluates the arguments to a proc call, returning the parameter types of the signature to call.
+/// Variadic procs get an array, others get exactly as many arguments as they declare, padded with nulls.
+fn write_proc_args(
+    the_proc: &Proc,
+    args: &[Expression],
+    data: &mut TranspilerData,
+    ins: &mut InstructionBlob,
+) -> Result<String, CompilerError> {
+    if the_proc.var_arg {
+        write_args_array(args, data, ins)?;
+        return Ok("object[]".to_owned());
+    }
+
+    let param_count = the_proc.parameters.len();
+    if args.len() > param_count {
+        return Err(format!("Too many arguments to {}()", the_proc.name).into());
+    }
+
+    for expr in args {
+        evaluate_expression(expr, data, ins)?;
+    }
+    // Arguments that weren't passed are null.
+    for _ in args.len()..param_count {
+        ins.instruction(Instruction::ldnull);
+    }
+    Ok(object_args(param_count))
+}
+
+/// Evaluates arguments into a new `object[]`, for calling variadic procs.
+fn write_args_array(
+    args: &[Expression],
+    data: &mut TranspilerData,
+    ins: &mut InstructionBlob,
+) -> Result<(), CompilerError> {
+    ins.instruction(Instruction::ldci4(args.len() as i32));
+    ins.instruction(Instruction::newarr("[mscorlib]System.Object".to_owned()));
+    for (i, expr) in args.iter().enumerate() {
+        ins.instruction(Instruction::dup);
+        ins.instruction(Instruction::ldci4(i as i32));
+        evaluate_expression(expr, data, ins)?;
+        ins.instruction(Instruction::stelemref);
+    }
+    Ok(())
+}
+
+/// The argument list of a proc signature taking `count` objects, like `object, object`.
+fn object_args(count: usize) -> String {
+    vec!["object"; count].join(", ")
+}
+
+/// A var on a type, as a CIL field.
+struct VarField {
+    /// The field signature, like `object byond_root/obj::'name'`.
+    field: String,
+    var_type: VariableType,
+    is_static: bool,
+}
+
+impl VarField {
+    /// Replaces the object owning the var on top of the stack with the var's value.
+    fn write_load(&self, ins: &mut InstructionBlob) {
+        if self.is_static {
+            ins.instruction(Instruction::pop);
+            ins.instruction(Instruction::ldsfld(self.field.clone()));
+        } else {
+            ins.instruction(Instruction::ldfld(self.field.clone()));
+        }
+        cast_to_type(&self.var_type, ins);
+    }
+
+    /// Stores the value on top of the stack into the var of the object below it.
+    /// Leaves the value on the stack.
+    fn write_store(&self, data: &mut TranspilerData, ins: &mut InstructionBlob) {
+        let temp = data.add_unnamed_local();
+        ins.instruction(Instruction::dup);
+        ins.instruction(Instruction::stloc(temp));
+        if self.is_static {
+            ins.instruction(Instruction::stsfld(self.field.clone()));
+            ins.instruction(Instruction::pop);
+        } else {
+            ins.instruction(Instruction::stfld(self.field.clone()));
+        }
+        ins.instruction(Instruction::ldloc(temp));
+    }
+}
+
+/// Gets the field backing a var on a type.
+fn get_var_field(
+    compiler_state: &CompilerState,
+    path: &ByondPath,
+    var_name: &str,
+) -> Option<VarField> {
+    let (declaring_type, type_var) = compiler_state.get_type_var(path, var_name)?;
+
+    Some(VarField {
+        field: format!(
+            "object {}::'{}'",
+            byond_path_to_class(declaring_type),
+            var_name
+        ),
+        var_type: type_var.var_type.clone(),
+        is_static: type_var.is_static,
+    })
+}
+
+/// Like `get_var_field`, but for a var on src if we're in an instance proc.
+fn get_src_var_field(data: &TranspilerData, var_name: &str) -> Option<VarField> {
+    if data.is_static {
+        return None;
+    }
+
+    let path = data.type_path.as_ref()?;
+    get_var_field(data.compiler_state, path, var_name)
+}
+
+fn is_list_path(path: &ByondPath) -> bool {
+    *path == ByondPath::from("/list")
+}
+
+/// Whether a value of this type can be a list, because it's untyped or a /list.
+fn may_be_list(var_type: &VariableType) -> bool {
+    match var_type {
+        VariableType::Unspecified => true,
+        VariableType::Object(path) => is_list_path(path),
+    }
+}
+
+/// Casts the object on top of the stack to the class of its declared type, if any.
+fn cast_to_type(var_type: &VariableType, ins: &mut InstructionBlob) {
+    match var_type {
+        // Lists are DmLists, not instances of the class compiled for /list.
+        VariableType::Object(path) if is_list_path(path) => {}
+        VariableType::Object(path) => {
+            ins.instruction(Instruction::castclass(byond_path_to_class(path)));
+        }
+        VariableType::Unspecified => {}
+    };
+}
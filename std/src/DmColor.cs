@@ -0,0 +1,92 @@
+using System;
+using System.Globalization;
+
+namespace DM
+{
+    /// <summary>
+    ///     Implements <c>rgb()</c> and parsing colors back with <c>rgb2num()</c>.
+    ///     Colors are text like <c>"#ff8000"</c>, with an optional alpha at the end.
+    /// </summary>
+    public static class DmColor
+    {
+        /// <summary>
+        ///     Implements <c>rgb(R, G, B, A)</c>. The alpha is only included if it's passed.
+        /// </summary>
+        public static object Rgb(object r, object g, object b, object a)
+        {
+            var color = "#" + Component(r, "R") + Component(g, "G") + Component(b, "B");
+            return a == null ? color : color + Component(a, "A");
+        }
+
+        /// <summary>
+        ///     Implements <c>rgb2num(color)</c>: a list of the red, green, blue and, if the color has one, alpha values.
+        ///     Accepts <c>#rgb</c>, <c>#rgba</c>, <c>#rrggbb</c> and <c>#rrggbbaa</c>.
+        /// </summary>
+        public static object Rgb2Num(object color, object space)
+        {
+            if (space != null && DmMath.ToNumber(space, "space") != 0)
+            {
+                throw new NotImplementedException("rgb2num() only supports the RGB color space.");
+            }
+
+            if (!(color is string text) || !TryParse(text, out var components))
+            {
+                throw new InvalidOperationException($"Invalid color: {DmText.Stringify(color)}");
+            }
+
+            var list = new DmList();
+            foreach (var component in components)
+            {
+                list.Append((float) component);
+            }
+
+            return list;
+        }
+
+        private static bool TryParse(string text, out int[] components)
+        {
+            components = null;
+            if (text.Length == 0 || text[0] != '#')
+            {
+                return false;
+            }
+
+            var digits = text.Substring(1);
+            int width;
+            switch (digits.Length)
+            {
+                case 3:
+                case 4:
+                    width = 1;
+                    break;
+                case 6:
+                case 8:
+                    width = 2;
+                    break;
+                default:
+                    return false;
+            }
+
+            components = new int[digits.Length / width];
+            for (var i = 0; i < components.Length; i++)
+            {
+                if (!int.TryParse(digits.Substring(i * width, width), NumberStyles.AllowHexSpecifier,
+                        CultureInfo.InvariantCulture, out var value))
+                {
+                    return false;
+                }
+
+                // #f80 is short for #ff8800.
+                components[i] = width == 1 ? value * 17 : value;
+            }
+
+            return true;
+        }
+
+        private static string Component(object value, string argument)
+        {
+            var component = (int) Math.Round(DmMath.ToNumber(value, argument));
+            return Math.Max(0, Math.Min(255, component)).ToString("x2", CultureInfo.InvariantCulture);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+using System;
+using System.Collections.Generic;
+using System.Globalization;
+using System.Linq;
+using System.Text.RegularExpressions;
+using System.Threading.Tasks;
+
+namespace DM
+{
+    /// <summary>
+    ///     <c>input()</c> and <c>alert()</c>, asking whoever runs the program on the console.
+    /// </summary>
+    /// <remarks>
+    ///     The asking proc sleeps while waiting for an answer, so other procs keep running.
+    /// </remarks>
+    public static class DmConsole
+    {
+        private static readonly Regex Color = new Regex("^#([0-9a-fA-F]{3}|[0-9a-fA-F]{6})$");
+
+        /// <summary>
+        ///     The kinds of answers <c>input() as ...</c> can get from the console.
+        /// </summary>
+        private static readonly string[] Supported =
+            {"text", "message", "password", "command_text", "num", "color", "anything", "null"};
+
+        /// <summary>
+        ///     Implements <c>input(Usr, Message, Title, Default) as Types in List</c>.
+        ///     <paramref name="types" /> is like <c>text|null</c>, empty without an <c>as</c> clause.
+        ///     Asks again until the answer fits the types. Gives back the default at the end of the input.
+        /// </summary>
+        public static object Input(object[] args, string types, object list)
+        {
+            args = WithoutUsr(args, 4);
+            var message = Arg(args, 0);
+            var title = Arg(args, 1);
+            var defaultValue = Arg(args, 2);
+
+            var allowed = new HashSet<string>(types.Split(new[] {'|'}, StringSplitOptions.RemoveEmptyEntries));
+            if (allowed.Count == 0 || allowed.SetEquals(new[] {"null"}))
+            {
+                allowed.Add(list == null ? "text" : "anything");
+            }
+
+            if (list == null && !allowed.Overlaps(Supported.Where(type => type != "null")))
+            {
+                throw new DmRuntimeException($"input() as {types} can't be answered on the console");
+            }
+
+            List<object> choices = null;
+            if (list != null)
+            {
+                choices = list is DmList dmList
+                    ? dmList.ToList()
+                    : throw new DmRuntimeException($"bad list for input(): {DmText.Stringify(list)}");
+            }
+
+            while (true)
+            {
+                if (title != null)
+                {
+                    Console.WriteLine(DmText.Stringify(title));
+                }
+
+                if (choices != null)
+                {
+                    for (var i = 0; i < choices.Count; i++)
+                    {
+                        Console.WriteLine($"  {i + 1}. {DmText.Stringify(choices[i])}");
+                    }
+                }
+
+                Console.Write(DmText.Stringify(message));
+                if (defaultValue != null)
+                {
+                    Console.Write($" [{DmText.Stringify(defaultValue)}]");
+                }
+
+                Console.Write(": ");
+
+                var line = ReadLine();
+                if (line == null)
+                {
+                    return defaultValue;
+                }
+
+                if (line.Length == 0 && (defaultValue != null || allowed.Contains("null")))
+                {
+                    return defaultValue;
+                }
+
+                if (choices != null ? TryChoose(choices, line, out var answer) : TryParse(line, allowed, out answer))
+                {
+                    return answer;
+                }
+
+                Console.WriteLine(choices != null ? "Pick one of the choices." : $"Please enter {Describe(allowed)}.");
+            }
+        }
+
+        /// <summary>
+        ///     Implements <c>alert(Usr, Message, Title, Button1 = "Ok", Button2, Button3)</c>.
+        ///     Returns the text of the chosen button, the first one at the end of the input.
+        /// </summary>
+        public static object Alert(object[] args)
+        {
+            args = WithoutUsr(args, 6);
+            var message = Arg(args, 0);
+            var title = Arg(args, 1);
+            var buttons = args.Skip(2).Where(button => button != null).ToList();
+            if (buttons.Count == 0)
+            {
+                buttons.Add("Ok");
+            }
+
+            while (true)
+            {
+                if (title != null)
+                {
+                    Console.WriteLine(DmText.Stringify(title));
+                }
+
+                Console.WriteLine(DmText.Stringify(message));
+                var options = buttons.Select((button, i) => $"[{i + 1}] {DmText.Stringify(button)}");
+                Console.Write(string.Join(" ", options) + ": ");
+
+                var line = ReadLine();
+                if (string.IsNullOrEmpty(line))
+                {
+                    return buttons[0];
+                }
+
+                if (TryChoose(buttons, line, out var chosen))
+                {
+                    return chosen;
+                }
+            }
+        }
+
+        /// <summary>
+        ///     Leaves out the first argument if it's <c>usr</c>, which is null with nobody playing,
+        ///     or when all <paramref name="max" /> arguments are passed.
+        /// </summary>
+        private static object[] WithoutUsr(object[] args, int max)
+        {
+            if (args.Length == max || args.Length > 1 && args[0] == null)
+            {
+                return args.Skip(1).ToArray();
+            }
+
+            return args;
+        }
+
+        private static object Arg(object[] args, int index)
+        {
+            return index < args.Length ? args[index] : null;
+        }
+
+        private static string ReadLine()
+        {
+            return DmScheduler.WaitFor(Task.Run(() => Console.In.ReadLine()));
+        }
+
+        /// <summary>
+        ///     Picks a choice by its number or its text, ignoring case.
+        /// </summary>
+        private static bool TryChoose(List<object> choices, string line, out object choice)
+        {
+            if (int.TryParse(line, NumberStyles.None, CultureInfo.InvariantCulture, out var number) &&
+                number >= 1 && number <= choices.Count)
+            {
+                choice = choices[number - 1];
+                return true;
+            }
+
+            choice = choices.FirstOrDefault(item =>
+                string.Equals(DmText.Stringify(item), line.Trim(), StringComparison.OrdinalIgnoreCase));
+            return choice != null;
+        }
+
+        private static bool TryParse(string line, HashSet<string> allowed, out object answer)
+        {
+            if (allowed.Contains("num") || allowed.Contains("anything"))
+            {
+                if (float.TryParse(line, NumberStyles.Float, CultureInfo.InvariantCulture, out var number))
+                {
+                    answer = number;
+                    return true;
+                }
+            }
+
+            if (allowed.Contains("color") && Color.IsMatch(line))
+            {
+                answer = line.ToLowerInvariant();
+                return true;
+            }
+
+            if (allowed.Overlaps(new[] {"text", "message", "password", "command_text", "anything"}))
+            {
+                answer = line;
+                return true;
+            }
+
+            answer = null;
+            return false;
+        }
+
+        private static string Describe(HashSet<string> allowed)
+        {
+            if (allowed.Contains("num") && !allowed.Overlaps(new[] {"text", "message", "anything"}))
+            {
+                return "a number";
+            }
+
+            return allowed.Contains("color") ? "a color like #ff0000" : "an answer";
+        }
+    }
+}
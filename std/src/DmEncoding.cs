@@ -0,0 +1,234 @@
+using System;
+using System.Globalization;
+using System.Net;
+using System.Security.Cryptography;
+using System.Text;
+
+namespace DM
+{
+    /// <summary>
+    ///     The hashing and encoding builtins.
+    /// </summary>
+    public static class DmEncoding
+    {
+        /// <summary>
+        ///     Implements <c>md5(T)</c>: the hash of the UTF-8 text as lowercase hex, or null if T isn't text.
+        /// </summary>
+        public static object Md5(object t)
+        {
+            using (var md5 = MD5.Create())
+            {
+                return Hash(md5, t);
+            }
+        }
+
+        /// <summary>
+        ///     Implements <c>sha1(T)</c>, like <c>md5()</c>.
+        /// </summary>
+        public static object Sha1(object t)
+        {
+            using (var sha1 = SHA1.Create())
+            {
+                return Hash(sha1, t);
+            }
+        }
+
+        /// <summary>
+        ///     Implements <c>url_encode(PlainText, format)</c>. Format 0 encodes everything but letters and digits,
+        ///     with spaces as <c>+</c>, like <c>list2params()</c>. Format 1 leaves the characters of a URL alone.
+        /// </summary>
+        public static object UrlEncode(object plainText, object format)
+        {
+            var text = DmText.Stringify(plainText);
+            if (DmMath.ToNumber(format, "format") != 0)
+            {
+#pragma warning disable 618
+                return Uri.EscapeUriString(text);
+#pragma warning restore 618
+            }
+
+            return EscapeParam(text);
+        }
+
+        public static object UrlDecode(object urlText)
+        {
+            return UnescapeParam(DmText.Stringify(urlText));
+        }
+
+        /// <summary>
+        ///     Implements <c>list2params(List)</c>: <c>list("a" = 1, "b")</c> becomes <c>"a=1&amp;b"</c>.
+        /// </summary>
+        public static object List2Params(object list)
+        {
+            if (!(list is DmList dmList))
+            {
+                return "";
+            }
+
+            var builder = new StringBuilder();
+            foreach (var key in dmList)
+            {
+                if (builder.Length != 0)
+                {
+                    builder.Append('&');
+                }
+
+                builder.Append(EscapeParam(DmText.Stringify(key)));
+                var value = dmList.GetAssoc(key);
+                if (value != null)
+                {
+                    builder.Append('=');
+                    builder.Append(EscapeParam(DmText.Stringify(value)));
+                }
+            }
+
+            return builder.ToString();
+        }
+
+        /// <summary>
+        ///     Implements <c>params2list(Params)</c>. Values stay text, a key given more than once
+        ///     gets a list of all its values.
+        /// </summary>
+        public static object Params2List(object parameters)
+        {
+            var list = new DmList();
+            if (!(parameters is string text))
+            {
+                return list;
+            }
+
+            foreach (var pair in text.Split('&', ';'))
+            {
+                if (pair.Length == 0)
+                {
+                    continue;
+                }
+
+                var equals = pair.IndexOf('=');
+                if (equals == -1)
+                {
+                    list.Append(UnescapeParam(pair));
+                    continue;
+                }
+
+                var key = UnescapeParam(pair.Substring(0, equals));
+                var value = UnescapeParam(pair.Substring(equals + 1));
+                switch (list.GetAssoc(key))
+                {
+                    case null:
+                        list.SetAssoc(key, value);
+                        break;
+                    case DmList values:
+                        values.Append(value);
+                        break;
+                    case var existing:
+                        var both = new DmList();
+                        both.Append(existing);
+                        both.Append(value);
+                        list.SetAssoc(key, both);
+                        break;
+                }
+            }
+
+            return list;
+        }
+
+        /// <summary>
+        ///     Implements <c>ckey(Key)</c>: lowercase, with everything but letters and digits removed.
+        /// </summary>
+        public static object CKey(object key)
+        {
+            return key is string text ? Canonical(text.ToLowerInvariant(), "") : null;
+        }
+
+        /// <summary>
+        ///     Implements <c>ckeyEx(Text)</c>: like <c>ckey()</c>, but keeping case, <c>-</c>, <c>_</c> and <c>@</c>.
+        /// </summary>
+        public static object CKeyEx(object text)
+        {
+            return text is string s ? Canonical(s, "-_@") : null;
+        }
+
+        /// <summary>
+        ///     Implements <c>html_encode(PlainText)</c>, escaping the characters special to HTML.
+        /// </summary>
+        public static object HtmlEncode(object plainText)
+        {
+            var builder = new StringBuilder();
+            foreach (var c in DmText.Stringify(plainText))
+            {
+                switch (c)
+                {
+                    case '&':
+                        builder.Append("&amp;");
+                        break;
+                    case '<':
+                        builder.Append("&lt;");
+                        break;
+                    case '>':
+                        builder.Append("&gt;");
+                        break;
+                    case '"':
+                        builder.Append("&quot;");
+                        break;
+                    case '\'':
+                        builder.Append("&#39;");
+                        break;
+                    default:
+                        builder.Append(c);
+                        break;
+                }
+            }
+
+            return builder.ToString();
+        }
+
+        /// <summary>
+        ///     Implements <c>html_decode(HtmlText)</c>, turning named and numeric entities back into characters.
+        /// </summary>
+        public static object HtmlDecode(object htmlText)
+        {
+            return WebUtility.HtmlDecode(DmText.Stringify(htmlText));
+        }
+
+        private static object Hash(HashAlgorithm algorithm, object t)
+        {
+            if (!(t is string text))
+            {
+                return null;
+            }
+
+            var builder = new StringBuilder();
+            foreach (var b in algorithm.ComputeHash(Encoding.UTF8.GetBytes(text)))
+            {
+                builder.Append(b.ToString("x2", CultureInfo.InvariantCulture));
+            }
+
+            return builder.ToString();
+        }
+
+        internal static string EscapeParam(string text)
+        {
+            return Uri.EscapeDataString(text).Replace("%20", "+");
+        }
+
+        private static string UnescapeParam(string text)
+        {
+            return Uri.UnescapeDataString(text.Replace('+', ' '));
+        }
+
+        private static string Canonical(string text, string keep)
+        {
+            var builder = new StringBuilder(text.Length);
+            foreach (var c in text)
+            {
+                if (c < 0x80 && char.IsLetterOrDigit(c) || keep.IndexOf(c) != -1)
+                {
+                    builder.Append(c);
+                }
+            }
+
+            return builder.ToString();
+        }
+    }
+}
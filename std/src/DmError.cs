@@ -0,0 +1,234 @@
+using System;
+using System.Collections.Generic;
+using System.Reflection;
+using System.Text;
+using Microsoft.CSharp.RuntimeBinder;
+
+namespace DM
+{
+    /// <summary>
+    ///     A DM runtime error, with the message BYOND would give for it.
+    /// </summary>
+    public sealed class DmRuntimeException : Exception
+    {
+        public DmRuntimeException(string message) : base(message)
+        {
+        }
+    }
+
+    /// <summary>
+    ///     Keeps track of the procs each DM thread is in, to report runtime errors like BYOND does.
+    /// </summary>
+    /// <remarks>
+    ///     A runtime error aborts the DM thread it happened on, the scheduler reports it here.
+    /// </remarks>
+    public static class DmError
+    {
+        [ThreadStatic] private static List<Frame> _frames;
+
+        private static List<Frame> Frames => _frames ?? (_frames = new List<Frame>());
+
+        /// <summary>
+        ///     Called by the generated code when a proc starts.
+        /// </summary>
+        public static void Enter(string name, string path, string file, int line, object src)
+        {
+            Frames.Add(new Frame(name, path, file, line, src));
+        }
+
+        /// <summary>
+        ///     Called by the generated code before each statement.
+        /// </summary>
+        public static void Line(int line)
+        {
+            var frames = Frames;
+            if (frames.Count != 0)
+            {
+                frames[frames.Count - 1].Line = line;
+            }
+        }
+
+        /// <summary>
+        ///     Called by the generated code when a proc returns.
+        /// </summary>
+        public static void Exit()
+        {
+            var frames = Frames;
+            if (frames.Count != 0)
+            {
+                frames.RemoveAt(frames.Count - 1);
+            }
+        }
+
+        /// <summary>
+        ///     Reports an error that aborted a DM thread: passed to <c>world/Error()</c> if the code overrides it,
+        ///     otherwise written to <c>world.log</c>.
+        /// </summary>
+        internal static void Report(Exception exception)
+        {
+            var message = Describe(exception);
+            var frames = TakeFrames();
+
+            var world = DmWorld.World;
+            if (world == null || !DmInternal.HasOverride(world, "Error"))
+            {
+                DmWorld.Log(Format(message, frames));
+                return;
+            }
+
+            try
+            {
+                DmInternal.CallOverride(world, "Error", new[] {CreateException(message, frames)});
+            }
+            catch (Exception handlerException)
+            {
+                // Nothing left to pass this one to.
+                DmWorld.Log(Format(message, frames));
+                DmWorld.Log(Format(Describe(handlerException), TakeFrames()));
+            }
+        }
+
+        /// <summary>
+        ///     Describes a value in an error message: text in quotes, objects as <c>the obj (/obj)</c>.
+        /// </summary>
+        internal static string DescribeValue(object value)
+        {
+            switch (value)
+            {
+                case null:
+                    return "null";
+                case string s:
+                    return $"\"{s}\"";
+            }
+
+            var path = DmPath.Of(value);
+            if (path == null)
+            {
+                return DmText.Stringify(value);
+            }
+
+            var name = DmVars.GetField(value, "name")?.GetValue(value) as string;
+            if (string.IsNullOrEmpty(name))
+            {
+                name = "the " + path.Path.Substring(path.Path.LastIndexOf('/') + 1);
+            }
+
+            return $"{name} ({path.Path})";
+        }
+
+        /// <summary>
+        ///     Gets the frames of the procs the error went through, innermost last, and clears them.
+        /// </summary>
+        private static Frame[] TakeFrames()
+        {
+            var frames = Frames.ToArray();
+            Frames.Clear();
+            return frames;
+        }
+
+        private static string Describe(Exception exception)
+        {
+            while (exception.InnerException != null &&
+                   (exception is TargetInvocationException || exception is TypeInitializationException))
+            {
+                exception = exception.InnerException;
+            }
+
+            switch (exception)
+            {
+                case RuntimeBinderException _:
+                case InvalidCastException _:
+                    return "type mismatch: " + exception.Message;
+                default:
+                    return exception.Message;
+            }
+        }
+
+        private static string Format(string message, Frame[] frames)
+        {
+            var builder = new StringBuilder();
+            builder.Append("runtime error: ").Append(message);
+            if (frames.Length == 0)
+            {
+                return builder.ToString();
+            }
+
+            var top = frames[frames.Length - 1];
+            builder.AppendLine();
+            builder.Append(Detail(top));
+            builder.AppendLine();
+            builder.Append("  call stack:");
+            for (var i = frames.Length - 1; i >= 0; i--)
+            {
+                builder.AppendLine();
+                var frame = frames[i];
+                if (frame.Src != null)
+                {
+                    builder.Append(DescribeValue(frame.Src)).Append(": ");
+                }
+
+                builder.Append(frame.Name).Append("()");
+            }
+
+            return builder.ToString();
+        }
+
+        /// <summary>
+        ///     The lines under the message, which also go in <c>desc</c> of the /exception.
+        /// </summary>
+        private static string Detail(Frame frame)
+        {
+            return $"proc name: {frame.Name} ({frame.Path})" + Environment.NewLine +
+                   $"  source file: {frame.File},{frame.Line}" + Environment.NewLine +
+                   "  usr: null" + Environment.NewLine +
+                   $"  src: {DescribeValue(frame.Src)}";
+        }
+
+        /// <summary>
+        ///     Creates the /exception passed to <c>world/Error()</c>.
+        /// </summary>
+        private static object CreateException(string message, Frame[] frames)
+        {
+            var path = DmPath.FromText("/exception");
+            if (path == null)
+            {
+                return message;
+            }
+
+            var exception = DmPath.New(path, new object[0]);
+            Set(exception, "name", message);
+            if (frames.Length != 0)
+            {
+                var top = frames[frames.Length - 1];
+                Set(exception, "desc", Detail(top));
+                Set(exception, "file", top.File);
+                Set(exception, "line", (float) top.Line);
+            }
+
+            return exception;
+        }
+
+        private static void Set(object obj, string name, object value)
+        {
+            DmVars.GetField(obj, name)?.SetValue(obj, value);
+        }
+
+        private sealed class Frame
+        {
+            public readonly string Name;
+            public readonly string Path;
+            public readonly string File;
+            public readonly object Src;
+            public int Line;
+
+            public Frame(string name, string path, string file, int line, object src)
+            {
+                Name = name;
+                Path = path;
+                File = file;
+                Line = line;
+                Src = src;
+            }
+        }
+    }
+}
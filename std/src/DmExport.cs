@@ -0,0 +1,98 @@
+using System;
+using System.Globalization;
+using System.IO;
+using System.Linq;
+using System.Net.Http;
+using System.Net.Sockets;
+using System.Threading.Tasks;
+
+namespace DM
+{
+    /// <summary>
+    ///     <c>world.Export()</c>, which calls other worlds through <c>byond://</c> addresses
+    ///     and web servers through <c>http://</c> ones.
+    /// </summary>
+    /// <remarks>
+    ///     The calling proc sleeps until the answer arrives, other procs keep running in the meantime.
+    /// </remarks>
+    public static class DmExport
+    {
+        private static readonly HttpClient Client = new HttpClient();
+
+        /// <summary>
+        ///     Implements <c>world.Export(Addr, File, Persist, Clients)</c>.
+        ///     A <c>byond://host:port?query</c> address returns what the <c>world/Topic()</c> there returned.
+        ///     An http address returns a list of the response headers, with <c>STATUS</c> and <c>CONTENT</c>.
+        ///     The file, or text, is sent as the body of a POST. Returns null if the other side can't be reached.
+        /// </summary>
+        public static object Export(object src, object address, object file, object persist, object clients)
+        {
+            var url = DmText.Stringify(address);
+            try
+            {
+                if (url.StartsWith("byond://", StringComparison.OrdinalIgnoreCase))
+                {
+                    return DmScheduler.WaitFor(Task.Run(() => Topic(url.Substring("byond://".Length))));
+                }
+
+                if (url.StartsWith("http://", StringComparison.OrdinalIgnoreCase) ||
+                    url.StartsWith("https://", StringComparison.OrdinalIgnoreCase))
+                {
+                    return DmScheduler.WaitFor(Http(url, file == null ? null : ContentOf(file)));
+                }
+            }
+            catch (Exception e) when (e is HttpRequestException || e is TaskCanceledException ||
+                                      e is SocketException || e is IOException)
+            {
+                return null;
+            }
+
+            throw new DmRuntimeException($"bad address for world.Export(): {url}");
+        }
+
+        /// <summary>
+        ///     Implements <c>world.Import()</c>, for the file sent along with a Topic call.
+        ///     Topic calls here never carry files, so there is nothing to import.
+        /// </summary>
+        public static object Import(object src)
+        {
+            return null;
+        }
+
+        private static object Topic(string address)
+        {
+            var query = address.IndexOf('?');
+            var hostPort = query == -1 ? address : address.Substring(0, query);
+            var colon = hostPort.LastIndexOf(':');
+            if (colon == -1 || !int.TryParse(hostPort.Substring(colon + 1), NumberStyles.None,
+                    CultureInfo.InvariantCulture, out var port))
+            {
+                throw new DmRuntimeException($"bad address for world.Export(): byond://{address}");
+            }
+
+            return DmTopic.Call(hostPort.Substring(0, colon), port, query == -1 ? "" : address.Substring(query));
+        }
+
+        private static async Task<object> Http(string url, string content)
+        {
+            var response = content == null
+                ? await Client.GetAsync(url)
+                : await Client.PostAsync(url, new StringContent(content));
+
+            var result = new DmList();
+            result.SetAssoc("STATUS", $"{(int) response.StatusCode} {response.ReasonPhrase}");
+            result.SetAssoc("CONTENT", await response.Content.ReadAsStringAsync());
+            foreach (var header in response.Headers.Concat(response.Content.Headers))
+            {
+                result.SetAssoc(header.Key, string.Join(", ", header.Value));
+            }
+
+            return result;
+        }
+
+        private static string ContentOf(object file)
+        {
+            return file is DmFile ? DmText.Stringify(DmFile.File2Text(file)) : DmText.Stringify(file);
+        }
+    }
+}
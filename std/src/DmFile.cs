@@ -0,0 +1,261 @@
+using System;
+using System.Collections.Generic;
+using System.IO;
+using System.Text;
+
+namespace DM
+{
+    /// <summary>
+    ///     A file reference, such as a <c>'resource.txt'</c> literal or the result of <c>file()</c>.
+    /// </summary>
+    /// <remarks>
+    ///     The file procs take either one of these or a path as text.
+    ///     Relative paths are resolved against the working directory, like DreamDaemon does.
+    ///     Paths ending in <c>/</c> are directories.
+    /// </remarks>
+    public sealed class DmFile
+    {
+        /// <summary>
+        ///     Where <c>F &gt;&gt; V</c> continues reading from.
+        /// </summary>
+        private int _readPosition;
+
+        public string Path { get; }
+
+        public DmFile(string path)
+        {
+            Path = path;
+        }
+
+        /// <summary>
+        ///     Implements <c>file(Path)</c>.
+        /// </summary>
+        public static object File(object path)
+        {
+            return path as DmFile ?? new DmFile(GetPath(path));
+        }
+
+        /// <summary>
+        ///     Implements <c>file2text(File)</c>: the contents of the file, or null if it doesn't exist.
+        /// </summary>
+        public static object File2Text(object file)
+        {
+            var fullPath = FullPath(file);
+            return System.IO.File.Exists(fullPath) ? System.IO.File.ReadAllText(fullPath) : null;
+        }
+
+        /// <summary>
+        ///     Implements <c>text2file(Text, File)</c>, appending the text to the file.
+        /// </summary>
+        public static object Text2File(object text, object file)
+        {
+            return DmInternal.Bool(TryWrite(file, DmText.Stringify(text)));
+        }
+
+        public static object FExists(object file)
+        {
+            var fullPath = FullPath(file);
+            return DmInternal.Bool(System.IO.File.Exists(fullPath) || Directory.Exists(fullPath));
+        }
+
+        /// <summary>
+        ///     Implements <c>fdel(File)</c>. Directories are deleted along with everything in them.
+        /// </summary>
+        public static object FDel(object file)
+        {
+            var fullPath = FullPath(file);
+            try
+            {
+                if (Directory.Exists(fullPath))
+                {
+                    Directory.Delete(fullPath, true);
+                    return DmInternal.Bool(true);
+                }
+
+                if (System.IO.File.Exists(fullPath))
+                {
+                    System.IO.File.Delete(fullPath);
+                    return DmInternal.Bool(true);
+                }
+            }
+            catch (IOException)
+            {
+            }
+            catch (UnauthorizedAccessException)
+            {
+            }
+
+            return DmInternal.Bool(false);
+        }
+
+        /// <summary>
+        ///     Implements <c>fcopy(Src, Dst)</c>, overwriting the destination. Directories are copied recursively.
+        /// </summary>
+        public static object FCopy(object source, object destination)
+        {
+            var sourcePath = FullPath(source);
+            var destinationPath = FullPath(destination);
+            try
+            {
+                if (Directory.Exists(sourcePath))
+                {
+                    CopyDirectory(sourcePath, destinationPath);
+                    return DmInternal.Bool(true);
+                }
+
+                if (System.IO.File.Exists(sourcePath))
+                {
+                    CreateParent(destinationPath);
+                    System.IO.File.Copy(sourcePath, destinationPath, true);
+                    return DmInternal.Bool(true);
+                }
+            }
+            catch (IOException)
+            {
+            }
+            catch (UnauthorizedAccessException)
+            {
+            }
+
+            return DmInternal.Bool(false);
+        }
+
+        /// <summary>
+        ///     Implements <c>flist(Path)</c>: the names of the files in a directory that start with the part
+        ///     after the last <c>/</c>. Directories end with <c>/</c>.
+        /// </summary>
+        public static object FList(object path)
+        {
+            var text = GetPath(path);
+            var split = text.LastIndexOf('/') + 1;
+            var directory = System.IO.Path.GetFullPath(split == 0 ? "." : text.Substring(0, split));
+            var prefix = text.Substring(split);
+
+            var list = new DmList();
+            if (!Directory.Exists(directory))
+            {
+                return list;
+            }
+
+            var names = new List<string>();
+            foreach (var entry in Directory.GetDirectories(directory))
+            {
+                names.Add(System.IO.Path.GetFileName(entry) + "/");
+            }
+
+            foreach (var entry in Directory.GetFiles(directory))
+            {
+                names.Add(System.IO.Path.GetFileName(entry));
+            }
+
+            names.Sort(string.CompareOrdinal);
+            foreach (var name in names)
+            {
+                if (name.StartsWith(prefix, StringComparison.Ordinal))
+                {
+                    list.Append(name);
+                }
+            }
+
+            return list;
+        }
+
+        /// <summary>
+        ///     Implements <c>F &gt;&gt; V</c>: the next line of the file, or null at the end.
+        /// </summary>
+        internal object ReadLine()
+        {
+            if (!(File2Text(this) is string text) || _readPosition >= text.Length)
+            {
+                return null;
+            }
+
+            var end = text.IndexOf('\n', _readPosition);
+            if (end == -1)
+            {
+                end = text.Length;
+            }
+
+            var line = text.Substring(_readPosition, end - _readPosition).TrimEnd('\r');
+            _readPosition = end + 1;
+            return line;
+        }
+
+        /// <summary>
+        ///     Implements <c>F &lt;&lt; text</c>, which appends a line to the file.
+        /// </summary>
+        internal void WriteLine(object value)
+        {
+            if (!TryWrite(this, DmText.Stringify(value) + "\n"))
+            {
+                throw new InvalidOperationException($"Cannot write to {Path}.");
+            }
+        }
+
+        public override string ToString()
+        {
+            return Path;
+        }
+
+        private static string GetPath(object file)
+        {
+            switch (file)
+            {
+                case DmFile dmFile:
+                    return dmFile.Path;
+                case string path:
+                    return path;
+                default:
+                    throw new InvalidOperationException($"Expected a file, got {DmText.Stringify(file)}.");
+            }
+        }
+
+        internal static string FullPath(object file)
+        {
+            return System.IO.Path.GetFullPath(GetPath(file));
+        }
+
+        private static bool TryWrite(object file, string text)
+        {
+            var fullPath = FullPath(file);
+            try
+            {
+                CreateParent(fullPath);
+                System.IO.File.AppendAllText(fullPath, text, new UTF8Encoding(false));
+                return true;
+            }
+            catch (IOException)
+            {
+                return false;
+            }
+            catch (UnauthorizedAccessException)
+            {
+                return false;
+            }
+        }
+
+        private static void CreateParent(string fullPath)
+        {
+            var parent = System.IO.Path.GetDirectoryName(fullPath);
+            if (!string.IsNullOrEmpty(parent))
+            {
+                Directory.CreateDirectory(parent);
+            }
+        }
+
+        private static void CopyDirectory(string source, string destination)
+        {
+            Directory.CreateDirectory(destination);
+            foreach (var file in Directory.GetFiles(source))
+            {
+                System.IO.File.Copy(file, System.IO.Path.Combine(destination, System.IO.Path.GetFileName(file)),
+                    true);
+            }
+
+            foreach (var directory in Directory.GetDirectories(source))
+            {
+                CopyDirectory(directory, System.IO.Path.Combine(destination, System.IO.Path.GetFileName(directory)));
+            }
+        }
+    }
+}
@@ -1,7 +1,283 @@
+using System;
+using System.IO;
+using System.Reflection;
+using Microsoft.CSharp.RuntimeBinder;
+
 namespace DM
 {
     public static class DmInternal
     {
+        private static readonly object True = 1f;
+        private static readonly object False = 0f;
+
+        /// <summary>
+        ///     Converts a bool to a DM number, because DM doesn't have bools.
+        /// </summary>
+        public static object Bool(bool x)
+        {
+            return x ? True : False;
+        }
+
+        /// <summary>
+        ///     Implements <c>A + B</c>. Text gets joined together, null is ignored, and everything else
+        ///     goes through the usual operator lookup. Text and anything else but null is a type mismatch.
+        /// </summary>
+        public static object Add(object a, object b)
+        {
+            if (a is string || b is string)
+            {
+                if ((a == null || a is string) && (b == null || b is string))
+                {
+                    return (string) a + (string) b;
+                }
+
+                throw TypeMismatch(a, "+", b);
+            }
+
+            if (a == null)
+            {
+                return b;
+            }
+
+            if (b == null)
+            {
+                return a;
+            }
+
+            try
+            {
+                return (dynamic) a + (dynamic) b;
+            }
+            catch (RuntimeBinderException)
+            {
+                throw TypeMismatch(a, "+", b);
+            }
+        }
+
+        /// <summary>
+        ///     Implements <c>A - B</c>.
+        /// </summary>
+        public static object Sub(object a, object b)
+        {
+            return Arithmetic(a, "-", b);
+        }
+
+        /// <summary>
+        ///     Implements <c>A * B</c>.
+        /// </summary>
+        public static object Mul(object a, object b)
+        {
+            return Arithmetic(a, "*", b);
+        }
+
+        /// <summary>
+        ///     Implements <c>A / B</c>.
+        /// </summary>
+        public static object Div(object a, object b)
+        {
+            return Arithmetic(a, "/", b);
+        }
+
+        /// <summary>
+        ///     Implements <c>A % B</c>.
+        /// </summary>
+        public static object Mod(object a, object b)
+        {
+            return Arithmetic(a, "%", b);
+        }
+
+        /// <summary>
+        ///     Numbers, with null counting as 0, and everything else through the usual operator lookup,
+        ///     like removing things from a list.
+        /// </summary>
+        private static object Arithmetic(object a, string op, object b)
+        {
+            if (IsNumber(a) && IsNumber(b))
+            {
+                var x = Convert.ToSingle(a);
+                var y = Convert.ToSingle(b);
+                if (y == 0 && (op == "/" || op == "%"))
+                {
+                    throw new DmRuntimeException("Division by zero");
+                }
+
+                switch (op)
+                {
+                    case "-":
+                        return x - y;
+                    case "*":
+                        return x * y;
+                    case "/":
+                        return x / y;
+                    default:
+                        return x % y;
+                }
+            }
+
+            try
+            {
+                switch (op)
+                {
+                    case "-":
+                        return (dynamic) a - (dynamic) b;
+                    case "*":
+                        return (dynamic) a * (dynamic) b;
+                    case "/":
+                        return (dynamic) a / (dynamic) b;
+                    default:
+                        return (dynamic) a % (dynamic) b;
+                }
+            }
+            catch (RuntimeBinderException)
+            {
+                throw TypeMismatch(a, op, b);
+            }
+        }
+
+        private static bool IsNumber(object value)
+        {
+            return value == null || value is float || value is int || value is double;
+        }
+
+        private static DmRuntimeException TypeMismatch(object a, string op, object b)
+        {
+            return new DmRuntimeException(
+                $"type mismatch: {DmError.DescribeValue(a)} {op} {DmError.DescribeValue(b)}");
+        }
+
+        /// <summary>
+        ///     Implements <c>A &lt;&lt; B</c>: a line written to a file, a value written to a savefile,
+        ///     or otherwise a call to <c>A.output(B)</c>, like <c>world &lt;&lt; "text"</c>.
+        /// </summary>
+        public static object Output(object target, object value)
+        {
+            switch (target)
+            {
+                case DmFile file:
+                    file.WriteLine(value);
+                    break;
+                case TextWriter writer:
+                    // world.log when it's standard error.
+                    writer.WriteLine(DmText.Stringify(value));
+                    break;
+                case null:
+                    throw new NullReferenceException("Cannot output to null.");
+                default:
+                    if (DmSavefile.IsSavefile(target))
+                    {
+                        DmSavefile.Write(target, null, value);
+                    }
+                    else
+                    {
+                        ((dynamic) target).output(value);
+                    }
+
+                    break;
+            }
+
+            return null;
+        }
+
+        /// <summary>
+        ///     Implements <c>A[index] &lt;&lt; B</c>, which writes to a key of a savefile.
+        /// </summary>
+        public static object OutputIndex(object target, object index, object value)
+        {
+            if (DmSavefile.IsSavefile(target))
+            {
+                DmSavefile.Write(target, index, value);
+                return null;
+            }
+
+            return Output(DmList.Index(target, index), value);
+        }
+
+        /// <summary>
+        ///     Implements <c>A &gt;&gt; V</c>, getting the value to assign to V:
+        ///     the next line of a file, or the value in a savefile.
+        /// </summary>
+        public static object Input(object source)
+        {
+            if (source is DmFile file)
+            {
+                return file.ReadLine();
+            }
+
+            if (DmSavefile.IsSavefile(source))
+            {
+                return DmSavefile.Read(source, null);
+            }
+
+            throw new InvalidOperationException($"Cannot read from {DmText.Stringify(source)}.");
+        }
+
+        /// <summary>
+        ///     Implements <c>A[index] &gt;&gt; V</c>, which reads a key of a savefile.
+        /// </summary>
+        public static object InputIndex(object source, object index)
+        {
+            if (DmSavefile.IsSavefile(source))
+            {
+                return DmSavefile.Read(source, index);
+            }
+
+            return Input(DmList.Index(source, index));
+        }
+
+        /// <summary>
+        ///     Implements <c>del(X)</c>: calls the <c>Del()</c> proc of the object. Deleting the world shuts it down.
+        /// </summary>
+        public static object Del(object target)
+        {
+            if (target == null)
+            {
+                return null;
+            }
+
+            if (target == DmWorld.World)
+            {
+                DmWorld.Delete();
+            }
+            else
+            {
+                CallOverride(target, "Del", new object[0]);
+            }
+
+            return null;
+        }
+
+        /// <summary>
+        ///     Calls a proc the DM code defined on an object, like <c>world/New()</c>.
+        ///     Does nothing if the code didn't, since the builtin ones don't do anything.
+        /// </summary>
+        internal static object CallOverride(object obj, string name, object[] args)
+        {
+            return FindOverride(obj, name)?.Invoke(obj, new object[] {args});
+        }
+
+        internal static bool HasOverride(object obj, string name)
+        {
+            return FindOverride(obj, name) != null;
+        }
+
+        /// <summary>
+        ///     Procs written in DM are instance methods taking an arguments array,
+        ///     unlike the stubs for the builtin procs that aren't implemented.
+        /// </summary>
+        private static MethodInfo FindOverride(object obj, string name)
+        {
+            return obj.GetType().GetMethod(name, BindingFlags.Public | BindingFlags.Instance, null,
+                new[] {typeof(object[])}, null);
+        }
+
+        /// <summary>
+        ///     Gets an argument passed to a proc, or null if it wasn't passed.
+        /// </summary>
+        public static object Arg(object[] args, int index)
+        {
+            return args != null && index < args.Length ? args[index] : null;
+        }
+
         public static bool Truthy(object x)
         {
             if (x is double d)
@@ -0,0 +1,423 @@
+using System;
+using System.Collections.Generic;
+using System.Globalization;
+using System.Text;
+
+namespace DM
+{
+    /// <summary>
+    ///     Implements <c>json_encode()</c> and <c>json_decode()</c>.
+    /// </summary>
+    /// <remarks>
+    ///     Associative lists are JSON objects, other lists are arrays.
+    ///     JSON has no booleans in DM, <c>true</c> and <c>false</c> decode to 1 and 0.
+    /// </remarks>
+    public static class DmJson
+    {
+        /// <summary>
+        ///     <c>JSON_PRETTY_PRINT</c>, the flag to indent the output of <c>json_encode()</c>.
+        /// </summary>
+        public const int PrettyPrint = 1;
+
+        public static object Encode(object value, object flags)
+        {
+            var pretty = ((int) DmMath.ToNumber(flags, "flags") & PrettyPrint) != 0;
+            var builder = new StringBuilder();
+            Write(builder, value, pretty, 0, new HashSet<DmList>());
+            return builder.ToString();
+        }
+
+        public static object Decode(object text, object flags)
+        {
+            if (!(text is string json))
+            {
+                throw new InvalidOperationException("json_decode() needs text.");
+            }
+
+            var reader = new Reader(json);
+            var value = reader.ReadValue();
+            reader.SkipWhitespace();
+            if (!reader.AtEnd)
+            {
+                throw reader.Error("Unexpected text after the value");
+            }
+
+            return value;
+        }
+
+        private static void Write(StringBuilder builder, object value, bool pretty, int depth,
+            HashSet<DmList> encoding)
+        {
+            switch (value)
+            {
+                case null:
+                    builder.Append("null");
+                    break;
+                case float f:
+                    WriteNumber(builder, f);
+                    break;
+                case int i:
+                    WriteNumber(builder, i);
+                    break;
+                case double d:
+                    WriteNumber(builder, (float) d);
+                    break;
+                case DmList list:
+                    // Lists inside themselves would go on forever.
+                    if (!encoding.Add(list))
+                    {
+                        throw new InvalidOperationException("Cannot encode a list that contains itself.");
+                    }
+
+                    if (list.IsAssociative)
+                    {
+                        WriteObject(builder, list, pretty, depth, encoding);
+                    }
+                    else
+                    {
+                        WriteArray(builder, list, pretty, depth, encoding);
+                    }
+
+                    encoding.Remove(list);
+                    break;
+                default:
+                    WriteString(builder, DmText.Stringify(value));
+                    break;
+            }
+        }
+
+        private static void WriteArray(StringBuilder builder, DmList list, bool pretty, int depth,
+            HashSet<DmList> encoding)
+        {
+            builder.Append('[');
+            var first = true;
+            foreach (var item in list)
+            {
+                WriteSeparator(builder, pretty, depth + 1, ref first);
+                Write(builder, item, pretty, depth + 1, encoding);
+            }
+
+            WriteClose(builder, ']', pretty, depth, first);
+        }
+
+        private static void WriteObject(StringBuilder builder, DmList list, bool pretty, int depth,
+            HashSet<DmList> encoding)
+        {
+            builder.Append('{');
+            var first = true;
+            foreach (var key in list)
+            {
+                WriteSeparator(builder, pretty, depth + 1, ref first);
+                WriteString(builder, DmText.Stringify(key));
+                builder.Append(pretty ? ": " : ":");
+                Write(builder, list.GetAssoc(key), pretty, depth + 1, encoding);
+            }
+
+            WriteClose(builder, '}', pretty, depth, first);
+        }
+
+        private static void WriteSeparator(StringBuilder builder, bool pretty, int depth, ref bool first)
+        {
+            if (!first)
+            {
+                builder.Append(',');
+            }
+
+            first = false;
+            if (pretty)
+            {
+                WriteIndent(builder, depth);
+            }
+        }
+
+        private static void WriteClose(StringBuilder builder, char close, bool pretty, int depth, bool empty)
+        {
+            if (pretty && !empty)
+            {
+                WriteIndent(builder, depth);
+            }
+
+            builder.Append(close);
+        }
+
+        private static void WriteIndent(StringBuilder builder, int depth)
+        {
+            builder.Append('\n');
+            builder.Append(' ', depth * 4);
+        }
+
+        private static void WriteNumber(StringBuilder builder, float number)
+        {
+            if (float.IsNaN(number) || float.IsInfinity(number))
+            {
+                // JSON can't represent these.
+                builder.Append("null");
+                return;
+            }
+
+            builder.Append(number.ToString("R", CultureInfo.InvariantCulture));
+        }
+
+        private static void WriteString(StringBuilder builder, string text)
+        {
+            builder.Append('"');
+            foreach (var c in text)
+            {
+                switch (c)
+                {
+                    case '"':
+                        builder.Append("\\\"");
+                        break;
+                    case '\\':
+                        builder.Append("\\\\");
+                        break;
+                    case '\n':
+                        builder.Append("\\n");
+                        break;
+                    case '\r':
+                        builder.Append("\\r");
+                        break;
+                    case '\t':
+                        builder.Append("\\t");
+                        break;
+                    default:
+                        if (c < 0x20)
+                        {
+                            builder.AppendFormat(CultureInfo.InvariantCulture, "\\u{0:x4}", (int) c);
+                        }
+                        else
+                        {
+                            builder.Append(c);
+                        }
+
+                        break;
+                }
+            }
+
+            builder.Append('"');
+        }
+
+        private sealed class Reader
+        {
+            private readonly string _text;
+            private int _pos;
+
+            public Reader(string text)
+            {
+                _text = text;
+            }
+
+            public bool AtEnd => _pos >= _text.Length;
+
+            public object ReadValue()
+            {
+                SkipWhitespace();
+                if (AtEnd)
+                {
+                    throw Error("Unexpected end of text");
+                }
+
+                switch (_text[_pos])
+                {
+                    case '{':
+                        return ReadObject();
+                    case '[':
+                        return ReadArray();
+                    case '"':
+                        return ReadString();
+                    case 't':
+                        Expect("true");
+                        return 1f;
+                    case 'f':
+                        Expect("false");
+                        return 0f;
+                    case 'n':
+                        Expect("null");
+                        return null;
+                    default:
+                        return ReadNumber();
+                }
+            }
+
+            public void SkipWhitespace()
+            {
+                while (!AtEnd && char.IsWhiteSpace(_text[_pos]))
+                {
+                    _pos++;
+                }
+            }
+
+            public Exception Error(string message)
+            {
+                return new InvalidOperationException($"JSON decoding error: {message} at position {_pos + 1}.");
+            }
+
+            private DmList ReadObject()
+            {
+                var list = new DmList();
+                _pos++;
+                SkipWhitespace();
+                if (TryConsume('}'))
+                {
+                    return list;
+                }
+
+                do
+                {
+                    SkipWhitespace();
+                    if (AtEnd || _text[_pos] != '"')
+                    {
+                        throw Error("Expected a key");
+                    }
+
+                    var key = ReadString();
+                    SkipWhitespace();
+                    if (!TryConsume(':'))
+                    {
+                        throw Error("Expected ':'");
+                    }
+
+                    list.SetAssoc(key, ReadValue());
+                    SkipWhitespace();
+                } while (TryConsume(','));
+
+                if (!TryConsume('}'))
+                {
+                    throw Error("Expected ',' or '}'");
+                }
+
+                return list;
+            }
+
+            private DmList ReadArray()
+            {
+                var list = new DmList();
+                _pos++;
+                SkipWhitespace();
+                if (TryConsume(']'))
+                {
+                    return list;
+                }
+
+                do
+                {
+                    list.Append(ReadValue());
+                    SkipWhitespace();
+                } while (TryConsume(','));
+
+                if (!TryConsume(']'))
+                {
+                    throw Error("Expected ',' or ']'");
+                }
+
+                return list;
+            }
+
+            private string ReadString()
+            {
+                var builder = new StringBuilder();
+                _pos++;
+                while (true)
+                {
+                    if (AtEnd)
+                    {
+                        throw Error("Unterminated string");
+                    }
+
+                    var c = _text[_pos++];
+                    if (c == '"')
+                    {
+                        return builder.ToString();
+                    }
+
+                    if (c != '\\')
+                    {
+                        builder.Append(c);
+                        continue;
+                    }
+
+                    if (AtEnd)
+                    {
+                        throw Error("Unterminated string");
+                    }
+
+                    var escape = _text[_pos++];
+                    switch (escape)
+                    {
+                        case '"':
+                        case '\\':
+                        case '/':
+                            builder.Append(escape);
+                            break;
+                        case 'b':
+                            builder.Append('\b');
+                            break;
+                        case 'f':
+                            builder.Append('\f');
+                            break;
+                        case 'n':
+                            builder.Append('\n');
+                            break;
+                        case 'r':
+                            builder.Append('\r');
+                            break;
+                        case 't':
+                            builder.Append('\t');
+                            break;
+                        case 'u':
+                            if (_pos + 4 > _text.Length || !int.TryParse(_text.Substring(_pos, 4),
+                                    NumberStyles.HexNumber, CultureInfo.InvariantCulture, out var code))
+                            {
+                                throw Error("Invalid unicode escape");
+                            }
+
+                            builder.Append((char) code);
+                            _pos += 4;
+                            break;
+                        default:
+                            throw Error($"Invalid escape '\\{escape}'");
+                    }
+                }
+            }
+
+            private object ReadNumber()
+            {
+                var start = _pos;
+                while (!AtEnd && "+-0123456789.eE".IndexOf(_text[_pos]) != -1)
+                {
+                    _pos++;
+                }
+
+                if (!float.TryParse(_text.Substring(start, _pos - start), NumberStyles.Float,
+                        CultureInfo.InvariantCulture, out var number))
+                {
+                    _pos = start;
+                    throw Error("Unexpected character");
+                }
+
+                return number;
+            }
+
+            private void Expect(string word)
+            {
+                if (string.CompareOrdinal(_text, _pos, word, 0, word.Length) != 0)
+                {
+                    throw Error("Unexpected character");
+                }
+
+                _pos += word.Length;
+            }
+
+            private bool TryConsume(char c)
+            {
+                if (AtEnd || _text[_pos] != c)
+                {
+                    return false;
+                }
+
+                _pos++;
+                return true;
+            }
+        }
+    }
+}
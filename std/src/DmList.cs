@@ -0,0 +1,279 @@
+using System;
+using System.Collections;
+using System.Collections.Generic;
+
+namespace DM
+{
+    /// <summary>
+    ///     A DM <c>/list</c>. Ordered, 1-indexed, and optionally associative.
+    /// </summary>
+    public class DmList : IEnumerable<object>
+    {
+        private readonly List<object> _items = new List<object>();
+        private readonly Dictionary<object, object> _assoc = new Dictionary<object, object>();
+
+        public int Count
+        {
+            get
+            {
+                Refresh();
+                return _items.Count;
+            }
+        }
+
+        /// <summary>
+        ///     Whether any key in the list has an associated value.
+        /// </summary>
+        public bool IsAssociative => _assoc.Count != 0;
+
+        public object this[int index]
+        {
+            get
+            {
+                Refresh();
+                return _items[index - 1];
+            }
+            set => _items[index - 1] = value;
+        }
+
+        /// <summary>
+        ///     Implements <c>args</c> in a proc.
+        /// </summary>
+        public static DmList FromArgs(object[] args)
+        {
+            var list = new DmList();
+            if (args != null)
+            {
+                list._items.AddRange(args);
+            }
+
+            return list;
+        }
+
+        /// <summary>
+        ///     Implements <c>L.len</c>. Objects that aren't lists can have a <c>len</c> var of their own.
+        /// </summary>
+        public static object Len(object list)
+        {
+            if (list is DmList dmList)
+            {
+                return (float) dmList.Count;
+            }
+
+            var field = list == null ? null : DmVars.GetField(list, "len");
+            if (field == null)
+            {
+                throw new DmRuntimeException($"Cannot read {DmError.DescribeValue(list)}.len");
+            }
+
+            return field.GetValue(list);
+        }
+
+        public void Append(object item)
+        {
+            _items.Add(item);
+        }
+
+        public bool Contains(object item)
+        {
+            Refresh();
+            return _items.Contains(item);
+        }
+
+        /// <summary>
+        ///     Gets the value associated with a key, or null if there is none.
+        /// </summary>
+        public virtual object GetAssoc(object key)
+        {
+            if (key == null)
+            {
+                return null;
+            }
+
+            _assoc.TryGetValue(key, out var value);
+            return value;
+        }
+
+        /// <summary>
+        ///     Implements <c>L[key] = value</c>, adding the key to the list if it's not in there yet.
+        /// </summary>
+        public virtual void SetAssoc(object key, object value)
+        {
+            if (key == null)
+            {
+                throw new ArgumentNullException(nameof(key));
+            }
+
+            if (!_assoc.ContainsKey(key) && !_items.Contains(key))
+            {
+                _items.Add(key);
+            }
+
+            _assoc[key] = value;
+        }
+
+        /// <summary>
+        ///     Implements <c>L[index]</c>, either by position or by key.
+        /// </summary>
+        public static object Index(object list, object index)
+        {
+            var dmList = AsList(list);
+            if (index is float f)
+            {
+                return dmList[CheckIndex(dmList, f)];
+            }
+
+            return dmList.GetAssoc(index);
+        }
+
+        /// <summary>
+        ///     Implements <c>L[index] = value</c>. Returns the value, as assignments are expressions.
+        /// </summary>
+        public static object SetIndex(object list, object index, object value)
+        {
+            var dmList = AsList(list);
+            if (index is float f)
+            {
+                dmList[CheckIndex(dmList, f)] = value;
+            }
+            else
+            {
+                dmList.SetAssoc(index, value);
+            }
+
+            return value;
+        }
+
+        private static DmList AsList(object list)
+        {
+            if (list is DmList dmList)
+            {
+                return dmList;
+            }
+
+            throw new InvalidOperationException($"Cannot index {list ?? "null"}, it is not a list.");
+        }
+
+        private static int CheckIndex(DmList list, float index)
+        {
+            var i = (int) index;
+            if (i < 1 || i > list.Count)
+            {
+                throw new IndexOutOfRangeException($"List index out of bounds: {index}");
+            }
+
+            return i;
+        }
+
+        /// <summary>
+        ///     <c>A + B</c>: a copy of A with B appended, or all items of B if it is a list.
+        /// </summary>
+        public static DmList operator +(DmList a, object b)
+        {
+            var list = a.Copy();
+            if (b is DmList other)
+            {
+                foreach (var item in other)
+                {
+                    list.Append(item);
+                }
+            }
+            else
+            {
+                list.Append(b);
+            }
+
+            return list;
+        }
+
+        /// <summary>
+        ///     <c>A - B</c>: a copy of A with B removed, or all items of B if it is a list.
+        ///     So <c>typesof(/datum) - /datum</c> gets just the subtypes.
+        /// </summary>
+        public static DmList operator -(DmList a, object b)
+        {
+            var list = a.Copy();
+            if (b is DmList other)
+            {
+                foreach (var item in other)
+                {
+                    list.RemoveOne(item);
+                }
+            }
+            else
+            {
+                list.RemoveOne(b);
+            }
+
+            return list;
+        }
+
+        public DmList Copy()
+        {
+            Refresh();
+            var list = new DmList();
+            foreach (var item in _items)
+            {
+                list._items.Add(item);
+            }
+
+            foreach (var pair in _assoc)
+            {
+                list._assoc[pair.Key] = pair.Value;
+            }
+
+            return list;
+        }
+
+        /// <summary>
+        ///     Removes the last occurence of an item, like DM does.
+        /// </summary>
+        private void RemoveOne(object item)
+        {
+            var index = _items.LastIndexOf(item);
+            if (index == -1)
+            {
+                return;
+            }
+
+            _items.RemoveAt(index);
+            if (item != null && !_items.Contains(item))
+            {
+                _assoc.Remove(item);
+            }
+        }
+
+        /// <summary>
+        ///     Called before the items are read, so lists that mirror something else can update them.
+        /// </summary>
+        protected virtual void Refresh()
+        {
+        }
+
+        /// <summary>
+        ///     Replaces all items, for use in <see cref="Refresh" />.
+        /// </summary>
+        protected void SetItems(IEnumerable<object> items)
+        {
+            _items.Clear();
+            _assoc.Clear();
+            _items.AddRange(items);
+        }
+
+        public IEnumerator<object> GetEnumerator()
+        {
+            Refresh();
+            return _items.GetEnumerator();
+        }
+
+        IEnumerator IEnumerable.GetEnumerator()
+        {
+            return GetEnumerator();
+        }
+
+        public override string ToString()
+        {
+            return "/list";
+        }
+    }
+}
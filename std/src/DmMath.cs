@@ -0,0 +1,239 @@
+using System;
+using System.Collections.Generic;
+
+namespace DM
+{
+    public static class DmMath
+    {
+        /// <summary>
+        ///     DM's trigonometry works in degrees.
+        /// </summary>
+        private const double DegreesToRadians = Math.PI / 180;
+
+        /// <summary>
+        ///     Implements <c>min(A, B, ...)</c> and <c>min(List)</c>.
+        /// </summary>
+        public static object Min(object[] args)
+        {
+            return Extreme(args, -1);
+        }
+
+        /// <summary>
+        ///     Implements <c>max(A, B, ...)</c> and <c>max(List)</c>.
+        /// </summary>
+        public static object Max(object[] args)
+        {
+            return Extreme(args, 1);
+        }
+
+        public static object Abs(object a)
+        {
+            return (float) Math.Abs(ToNumber(a, "A"));
+        }
+
+        public static object Sin(object x)
+        {
+            return (float) Math.Sin(ToNumber(x, "X") * DegreesToRadians);
+        }
+
+        public static object Cos(object x)
+        {
+            return (float) Math.Cos(ToNumber(x, "X") * DegreesToRadians);
+        }
+
+        public static object Tan(object x)
+        {
+            return (float) Math.Tan(ToNumber(x, "X") * DegreesToRadians);
+        }
+
+        public static object ArcSin(object x)
+        {
+            return (float) (Math.Asin(ToNumber(x, "X")) / DegreesToRadians);
+        }
+
+        public static object ArcCos(object x)
+        {
+            return (float) (Math.Acos(ToNumber(x, "X")) / DegreesToRadians);
+        }
+
+        /// <summary>
+        ///     Implements <c>arctan(A)</c>, and <c>arctan(x, y)</c> which gets the angle of a vector.
+        /// </summary>
+        public static object ArcTan(object a, object b)
+        {
+            if (b == null)
+            {
+                return (float) (Math.Atan(ToNumber(a, "A")) / DegreesToRadians);
+            }
+
+            return (float) (Math.Atan2(ToNumber(b, "y"), ToNumber(a, "x")) / DegreesToRadians);
+        }
+
+        public static object Sqrt(object a)
+        {
+            var number = ToNumber(a, "A");
+            if (number < 0)
+            {
+                throw new DmRuntimeException($"bad arg: cannot take the square root of {DmError.DescribeValue(a)}");
+            }
+
+            return (float) Math.Sqrt(number);
+        }
+
+        /// <summary>
+        ///     Implements <c>log(X)</c>, the natural logarithm, and <c>log(Base, X)</c>.
+        /// </summary>
+        public static object Log(object x, object y)
+        {
+            if (y == null)
+            {
+                return (float) CheckedLog(ToNumber(x, "X"));
+            }
+
+            return (float) (CheckedLog(ToNumber(y, "X")) / CheckedLog(ToNumber(x, "Base")));
+        }
+
+        /// <summary>
+        ///     Implements <c>round(A)</c>, which rounds down, and <c>round(A, B)</c>,
+        ///     which rounds to the nearest multiple of B.
+        /// </summary>
+        public static object Round(object a, object b)
+        {
+            var number = ToNumber(a, "A");
+            if (b == null)
+            {
+                return (float) Math.Floor(number);
+            }
+
+            var multiple = ToNumber(b, "B");
+            if (multiple == 0)
+            {
+                return (float) number;
+            }
+
+            return (float) (Math.Floor(number / multiple + 0.5) * multiple);
+        }
+
+        public static object Floor(object a)
+        {
+            return (float) Math.Floor(ToNumber(a, "A"));
+        }
+
+        public static object Ceil(object a)
+        {
+            return (float) Math.Ceiling(ToNumber(a, "A"));
+        }
+
+        public static object Trunc(object a)
+        {
+            return (float) Math.Truncate(ToNumber(a, "A"));
+        }
+
+        /// <summary>
+        ///     Implements <c>fract(A)</c>: the part after the decimal point, with the sign of A.
+        /// </summary>
+        public static object Fract(object a)
+        {
+            var number = ToNumber(a, "A");
+            return (float) (number - Math.Truncate(number));
+        }
+
+        public static object Clamp(object value, object low, object high)
+        {
+            var lowNumber = ToNumber(low, "Low");
+            var highNumber = ToNumber(high, "High");
+            return (float) Math.Max(lowNumber, Math.Min(highNumber, ToNumber(value, "Value")));
+        }
+
+        public static object Sign(object a)
+        {
+            return (float) Math.Sign(ToNumber(a, "A"));
+        }
+
+        public static object Lerp(object a, object b, object factor)
+        {
+            var from = ToNumber(a, "A");
+            return (float) (from + (ToNumber(b, "B") - from) * ToNumber(factor, "factor"));
+        }
+
+        /// <summary>
+        ///     Compares two values like DM's comparison operators do.
+        ///     Numbers compare as numbers and text compares alphabetically. Null counts as 0 next to a number,
+        ///     and as empty text next to text. Comparing a number with text is an error.
+        /// </summary>
+        public static int Compare(object a, object b)
+        {
+            if (a is string || b is string)
+            {
+                var textA = a == null ? "" : a as string;
+                var textB = b == null ? "" : b as string;
+                if (textA == null || textB == null)
+                {
+                    throw new DmRuntimeException(
+                        $"type mismatch: cannot compare {DmError.DescribeValue(a)} to {DmError.DescribeValue(b)}");
+                }
+
+                return string.CompareOrdinal(textA, textB);
+            }
+
+            return ToNumber(a, "A").CompareTo(ToNumber(b, "B"));
+        }
+
+        /// <summary>
+        ///     Gets the number passed as an argument, null counts as 0.
+        /// </summary>
+        internal static double ToNumber(object x, string argument)
+        {
+            switch (x)
+            {
+                case null:
+                    return 0;
+                case float f:
+                    return f;
+                case int i:
+                    return i;
+                case double d:
+                    return d;
+                default:
+                    throw new DmRuntimeException(
+                        $"bad arg: expected a number for {argument}, got {DmError.DescribeValue(x)}");
+            }
+        }
+
+        private static double CheckedLog(double x)
+        {
+            if (x <= 0)
+            {
+                throw new DmRuntimeException($"bad arg: cannot take the logarithm of {DmText.Stringify(x)}");
+            }
+
+            return Math.Log(x);
+        }
+
+        /// <summary>
+        ///     Finds the first value that no other value is further in <paramref name="direction" /> than.
+        /// </summary>
+        private static object Extreme(object[] args, int direction)
+        {
+            IEnumerable<object> values = args;
+            // A single list argument means the list contents.
+            if (args.Length == 1 && args[0] is DmList list)
+            {
+                values = list;
+            }
+
+            object result = null;
+            var first = true;
+            foreach (var value in values)
+            {
+                if (first || Math.Sign(Compare(value, result)) == direction)
+                {
+                    result = value;
+                    first = false;
+                }
+            }
+
+            return result;
+        }
+    }
+}
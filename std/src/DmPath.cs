@@ -0,0 +1,219 @@
+using System;
+using System.Collections.Generic;
+using System.Reflection;
+
+namespace DM
+{
+    /// <summary>
+    ///     A type path such as <c>/obj/item</c>, as a runtime value.
+    ///     Backed by the class the compiler generated for the type.
+    /// </summary>
+    /// <remarks>
+    ///     There is only ever one instance per type, so reference equality is path equality.
+    /// </remarks>
+    public sealed class DmPath
+    {
+        private static readonly Dictionary<Type, DmPath> ByType = new Dictionary<Type, DmPath>();
+        private static readonly Dictionary<string, DmPath> ByText = new Dictionary<string, DmPath>();
+
+        private readonly Dictionary<string, DmVarInfo> _vars = new Dictionary<string, DmVarInfo>();
+
+        public Type ClrType { get; }
+        public string Path { get; }
+
+        private DmPath(Type clrType, string path)
+        {
+            ClrType = clrType;
+            Path = path;
+        }
+
+        /// <summary>
+        ///     The type this one inherits from, or null for top level types.
+        /// </summary>
+        public DmPath Parent => FromType(ClrType.BaseType);
+
+        /// <summary>
+        ///     Every type path the program knows about.
+        /// </summary>
+        public static IEnumerable<DmPath> All => ByType.Values;
+
+        /// <summary>
+        ///     Gets the path for a compiled type, registering it if this is the first time we see it.
+        /// </summary>
+        public static DmPath Get(RuntimeTypeHandle handle, string path)
+        {
+            var type = Type.GetTypeFromHandle(handle);
+            if (ByType.TryGetValue(type, out var existing))
+            {
+                return existing;
+            }
+
+            var created = new DmPath(type, path);
+            ByType[type] = created;
+            ByText[path] = created;
+            return created;
+        }
+
+        public static DmPath FromType(Type type)
+        {
+            if (type == null)
+            {
+                return null;
+            }
+
+            ByType.TryGetValue(type, out var path);
+            return path;
+        }
+
+        /// <summary>
+        ///     Implements <c>text2path()</c>.
+        /// </summary>
+        public static DmPath FromText(object text)
+        {
+            if (!(text is string s))
+            {
+                return null;
+            }
+
+            ByText.TryGetValue(s, out var path);
+            return path;
+        }
+
+        /// <summary>
+        ///     Implements <c>x.type</c>.
+        /// </summary>
+        public static DmPath Of(object obj)
+        {
+            return obj == null ? null : FromType(obj.GetType());
+        }
+
+        /// <summary>
+        ///     Implements <c>x.parent_type</c>.
+        /// </summary>
+        public static DmPath ParentOf(object obj)
+        {
+            return Of(obj)?.Parent;
+        }
+
+        /// <summary>
+        ///     Implements <c>new T(...)</c> where <c>T</c> is a path stored in a variable.
+        /// </summary>
+        public static object New(object path, object[] args)
+        {
+            if (!(path is DmPath dmPath))
+            {
+                throw new InvalidOperationException($"Cannot create objects of type {path}.");
+            }
+
+            var instance = Activator.CreateInstance(dmPath.ClrType);
+
+            var newProc = dmPath.ClrType.GetMethod("New", BindingFlags.Public | BindingFlags.Instance);
+            if (newProc != null)
+            {
+                newProc.Invoke(instance, NewArguments(newProc, args));
+            }
+
+            return instance;
+        }
+
+        /// <summary>
+        ///     Procs written in DM take their arguments as one array,
+        ///     builtin ones take them one by one with null for those not passed.
+        /// </summary>
+        private static object[] NewArguments(MethodInfo newProc, object[] args)
+        {
+            var parameters = newProc.GetParameters();
+            if (parameters.Length == 1 && parameters[0].ParameterType == typeof(object[]))
+            {
+                return new object[] {args};
+            }
+
+            var arguments = new object[parameters.Length];
+            Array.Copy(args, arguments, Math.Min(args.Length, arguments.Length));
+            return arguments;
+        }
+
+        /// <summary>
+        ///     Implements <c>typesof(Item1, Item2, ...)</c>: the paths and all their subtypes, in path order.
+        /// </summary>
+        public static DmList TypesOf(object[] items)
+        {
+            return Subtypes(items, true);
+        }
+
+        /// <summary>
+        ///     Implements <c>subtypesof(Item1, Item2, ...)</c>: like <c>typesof()</c>, without the paths themselves.
+        /// </summary>
+        public static DmList SubtypesOf(object[] items)
+        {
+            return Subtypes(items, false);
+        }
+
+        private static DmList Subtypes(object[] items, bool includeItems)
+        {
+            // A type under more than one of the items is only in there once.
+            var types = new HashSet<DmPath>();
+            foreach (var item in items)
+            {
+                if (!(item is DmPath path))
+                {
+                    continue;
+                }
+
+                foreach (var other in All)
+                {
+                    if (other.IsSubtypeOf(path) && (includeItems || other != path))
+                    {
+                        types.Add(other);
+                    }
+                }
+            }
+
+            var sorted = new List<DmPath>(types);
+            sorted.Sort((a, b) => string.CompareOrdinal(a.Path, b.Path));
+
+            var list = new DmList();
+            foreach (var type in sorted)
+            {
+                list.Append(type);
+            }
+
+            return list;
+        }
+
+        /// <summary>
+        ///     Registers the compiled default value of a var declared or overriden on this type.
+        ///     Called by the generated code on startup.
+        /// </summary>
+        public void AddVar(string name, object initial, bool isSaved)
+        {
+            _vars[name] = new DmVarInfo(initial, isSaved);
+        }
+
+        /// <summary>
+        ///     Gets the metadata of a var on this type, looking at parent types if this one doesn't override it.
+        /// </summary>
+        public DmVarInfo GetVar(string name)
+        {
+            for (var path = this; path != null; path = path.Parent)
+            {
+                if (path._vars.TryGetValue(name, out var info))
+                {
+                    return info;
+                }
+            }
+
+            return null;
+        }
+
+        public bool IsSubtypeOf(DmPath other)
+        {
+            return other != null && (ClrType == other.ClrType || ClrType.IsSubclassOf(other.ClrType));
+        }
+
+        public override string ToString()
+        {
+            return Path;
+        }
+    }
+}
@@ -0,0 +1,164 @@
+using System;
+using System.Globalization;
+using System.Text.RegularExpressions;
+
+namespace DM
+{
+    /// <summary>
+    ///     The random number builtins. They all share one generator, so seeding it makes a program reproducible.
+    /// </summary>
+    /// <remarks>
+    ///     Set the <c>DM_RAND_SEED</c> environment variable to seed it on startup, like calling <c>rand_seed()</c>.
+    /// </remarks>
+    public static class DmRandom
+    {
+        private static readonly Regex DicePattern =
+            new Regex(@"^\s*(\d*)\s*d\s*(\d+)\s*(?:([+-])\s*(\d+))?\s*$", RegexOptions.IgnoreCase);
+
+        private static Random _random = CreateDefault();
+
+        /// <summary>
+        ///     Implements <c>rand()</c>, a number between 0 and 1,
+        ///     and <c>rand(L, H)</c>, a whole number between L and H inclusive. <c>rand(H)</c> starts at 0.
+        /// </summary>
+        public static object Rand(object low, object high)
+        {
+            if (low == null && high == null)
+            {
+                return (float) _random.NextDouble();
+            }
+
+            var l = ToInt(high == null ? null : low, "L");
+            var h = ToInt(high ?? low, "H");
+            if (l > h)
+            {
+                var swap = l;
+                l = h;
+                h = swap;
+            }
+
+            return (float) _random.Next(l, h + 1);
+        }
+
+        /// <summary>
+        ///     Implements <c>prob(P)</c>: true P percent of the time.
+        /// </summary>
+        public static object Prob(object p)
+        {
+            return DmInternal.Bool(_random.NextDouble() * 100 < DmMath.ToNumber(p, "P"));
+        }
+
+        /// <summary>
+        ///     Implements <c>pick(A, B, ...)</c> and <c>pick(List)</c>.
+        /// </summary>
+        public static object Pick(object[] args)
+        {
+            return PickWeighted(args, new object[args.Length]);
+        }
+
+        /// <summary>
+        ///     Implements <c>pick(prob(P1); A, prob(P2); B, ...)</c>.
+        ///     Choices without a weight get a weight of 100.
+        /// </summary>
+        public static object PickWeighted(object[] values, object[] weights)
+        {
+            if (values.Length == 1 && weights[0] == null && values[0] is DmList list)
+            {
+                return list.Count == 0 ? null : list[_random.Next(list.Count) + 1];
+            }
+
+            double total = 0;
+            foreach (var weight in weights)
+            {
+                total += Weight(weight);
+            }
+
+            var roll = _random.NextDouble() * total;
+            for (var i = 0; i < values.Length; i++)
+            {
+                roll -= Weight(weights[i]);
+                if (roll < 0)
+                {
+                    return values[i];
+                }
+            }
+
+            return values.Length == 0 ? null : values[values.Length - 1];
+        }
+
+        /// <summary>
+        ///     Implements <c>roll(Dice)</c> with dice like <c>"3d6+2"</c>, and <c>roll(ndice, sides)</c>.
+        /// </summary>
+        public static object Roll(object dice, object sides)
+        {
+            int count;
+            int sideCount;
+            var modifier = 0;
+
+            if (dice is string text)
+            {
+                var match = DicePattern.Match(text);
+                if (!match.Success)
+                {
+                    throw new InvalidOperationException($"Invalid dice: {text}");
+                }
+
+                count = match.Groups[1].Length == 0
+                    ? 1
+                    : int.Parse(match.Groups[1].Value, CultureInfo.InvariantCulture);
+                sideCount = int.Parse(match.Groups[2].Value, CultureInfo.InvariantCulture);
+                if (match.Groups[3].Success)
+                {
+                    modifier = int.Parse(match.Groups[4].Value, CultureInfo.InvariantCulture);
+                    if (match.Groups[3].Value == "-")
+                    {
+                        modifier = -modifier;
+                    }
+                }
+            }
+            else
+            {
+                count = ToInt(dice, "ndice");
+                sideCount = ToInt(sides, "sides");
+            }
+
+            var total = modifier;
+            for (var i = 0; i < count; i++)
+            {
+                total += sideCount <= 0 ? 0 : _random.Next(sideCount) + 1;
+            }
+
+            return (float) total;
+        }
+
+        /// <summary>
+        ///     Implements <c>rand_seed(Seed)</c>.
+        /// </summary>
+        public static object RandSeed(object seed)
+        {
+            _random = new Random(ToInt(seed, "Seed"));
+            return null;
+        }
+
+        private static Random CreateDefault()
+        {
+            var seed = Environment.GetEnvironmentVariable("DM_RAND_SEED");
+            if (seed != null && int.TryParse(seed, NumberStyles.Integer, CultureInfo.InvariantCulture, out var value))
+            {
+                return new Random(value);
+            }
+
+            return new Random();
+        }
+
+        private static double Weight(object weight)
+        {
+            return weight == null ? 100 : Math.Max(0, DmMath.ToNumber(weight, "prob"));
+        }
+
+        private static int ToInt(object value, string argument)
+        {
+            return (int) Math.Floor(DmMath.ToNumber(value, argument));
+        }
+    }
+}
@@ -0,0 +1,216 @@
+using System.Runtime.CompilerServices;
+using System.Text.RegularExpressions;
+
+namespace DM
+{
+    /// <summary>
+    ///     Implements the <c>/regex</c> datum. Its vars are regular fields on the compiled type,
+    ///     the procs here read and write them.
+    /// </summary>
+    /// <remarks>
+    ///     Flags: <c>g</c> to find from <c>next</c> and replace every match, <c>i</c> to ignore case,
+    ///     <c>m</c> to have <c>^</c> and <c>$</c> match at every line.
+    /// </remarks>
+    public static class DmRegex
+    {
+        private static readonly ConditionalWeakTable<object, CompiledRegex> Compiled =
+            new ConditionalWeakTable<object, CompiledRegex>();
+
+        /// <summary>
+        ///     Implements <c>regex(Pattern, flags)</c>, the same as <c>new /regex(Pattern, flags)</c>.
+        /// </summary>
+        public static object Create(object pattern, object flags)
+        {
+            return DmPath.New(DmPath.FromText("/regex"), new[] {pattern, flags});
+        }
+
+        /// <summary>
+        ///     Implements <c>/regex/New(pattern, flags)</c>. The pattern can also be another regex to copy.
+        /// </summary>
+        public static object New(object src, object pattern, object flags)
+        {
+            if (IsRegex(pattern))
+            {
+                flags = flags ?? Get(pattern, "flags");
+                pattern = Get(pattern, "name");
+            }
+
+            Set(src, "name", pattern);
+            Set(src, "flags", flags);
+            return null;
+        }
+
+        /// <summary>
+        ///     Implements <c>/regex/Find(haystack, Start, End)</c>.
+        ///     Returns the position of the match or 0, and sets <c>match</c>, <c>group</c>, <c>index</c> and <c>next</c>.
+        /// </summary>
+        public static object Find(object src, object haystack, object start, object end)
+        {
+            if (!(haystack is string text))
+            {
+                return 0f;
+            }
+
+            var units = new DmText.TextUnits(text, false);
+            if (start == null && IsGlobal(src))
+            {
+                start = Get(src, "next");
+            }
+
+            var from = units.Index(units.Resolve(start, 1));
+            var to = units.Index(units.Resolve(end, 0));
+            var match = to >= from ? GetRegex(src).Match(text, from, to - from) : Match.Empty;
+
+            Set(src, "text", text);
+            if (!match.Success)
+            {
+                Set(src, "index", 0f);
+                Set(src, "match", null);
+                Set(src, "group", null);
+                Set(src, "next", null);
+                return 0f;
+            }
+
+            var groups = new DmList();
+            for (var i = 1; i < match.Groups.Count; i++)
+            {
+                groups.Append(match.Groups[i].Success ? match.Groups[i].Value : null);
+            }
+
+            var index = (float) units.Position(match.Index);
+            var next = (float) units.Position(match.Index + match.Length);
+            Set(src, "index", index);
+            Set(src, "match", match.Value);
+            Set(src, "group", groups);
+            // Don't get stuck on empty matches.
+            Set(src, "next", match.Length == 0 ? next + 1 : next);
+            return index;
+        }
+
+        /// <summary>
+        ///     Implements <c>/regex/Replace(haystack, replacement, Start, End)</c>.
+        ///     The replacement can use <c>$1</c> to <c>$9</c> for groups and <c>$0</c> for the whole match.
+        /// </summary>
+        public static object Replace(object src, object haystack, object replacement, object start, object end)
+        {
+            if (!(haystack is string text))
+            {
+                return haystack;
+            }
+
+            var units = new DmText.TextUnits(text, false);
+            var from = units.Index(units.Resolve(start, 1));
+            var to = units.Index(units.Resolve(end, 0));
+            if (to < from)
+            {
+                return text;
+            }
+
+            var replaced = GetRegex(src).Replace(text.Substring(from, to - from), DmText.Stringify(replacement),
+                IsGlobal(src) ? -1 : 1);
+            var result = text.Substring(0, from) + replaced + text.Substring(to);
+
+            Set(src, "text", result);
+            Set(src, "next", null);
+            return result;
+        }
+
+        /// <summary>
+        ///     Implements <c>splittext()</c> with a regex as delimiter, splitting between <paramref name="from" />
+        ///     and <paramref name="to" />.
+        /// </summary>
+        internal static DmList Split(object regex, string text, int from, int to, bool includeDelimiters)
+        {
+            var list = new DmList();
+            var itemStart = 0;
+            if (to > from)
+            {
+                for (var match = GetRegex(regex).Match(text, from, to - from);
+                    match.Success;
+                    match = match.NextMatch())
+                {
+                    if (match.Length == 0)
+                    {
+                        continue;
+                    }
+
+                    list.Append(text.Substring(itemStart, match.Index - itemStart));
+                    if (includeDelimiters)
+                    {
+                        list.Append(match.Value);
+                    }
+
+                    itemStart = match.Index + match.Length;
+                }
+            }
+
+            list.Append(text.Substring(itemStart));
+            return list;
+        }
+
+        public static bool IsRegex(object value)
+        {
+            return DmPath.Of(value)?.IsSubtypeOf(DmPath.FromText("/regex")) == true;
+        }
+
+        private static bool IsGlobal(object src)
+        {
+            return Get(src, "flags") is string flags && flags.IndexOf('g') != -1;
+        }
+
+        /// <summary>
+        ///     Gets the compiled regex for the current pattern and flags, compiling it again if either changed.
+        /// </summary>
+        private static Regex GetRegex(object src)
+        {
+            var pattern = DmText.Stringify(Get(src, "name"));
+            var flags = DmText.Stringify(Get(src, "flags"));
+
+            if (Compiled.TryGetValue(src, out var compiled) && compiled.Pattern == pattern &&
+                compiled.Flags == flags)
+            {
+                return compiled.Regex;
+            }
+
+            var options = RegexOptions.None;
+            if (flags.IndexOf('i') != -1)
+            {
+                options |= RegexOptions.IgnoreCase;
+            }
+
+            if (flags.IndexOf('m') != -1)
+            {
+                options |= RegexOptions.Multiline;
+            }
+
+            compiled = new CompiledRegex(pattern, flags, new Regex(pattern, options));
+            Compiled.Remove(src);
+            Compiled.Add(src, compiled);
+            return compiled.Regex;
+        }
+
+        private static object Get(object src, string name)
+        {
+            return DmVars.GetField(src, name)?.GetValue(src);
+        }
+
+        private static void Set(object src, string name, object value)
+        {
+            DmVars.GetField(src, name)?.SetValue(src, value);
+        }
+
+        private sealed class CompiledRegex
+        {
+            public readonly string Flags;
+            public readonly string Pattern;
+            public readonly Regex Regex;
+
+            public CompiledRegex(string pattern, string flags, Regex regex)
+            {
+                Pattern = pattern;
+                Flags = flags;
+                Regex = regex;
+            }
+        }
+    }
+}
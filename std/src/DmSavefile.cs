@@ -0,0 +1,852 @@
+using System;
+using System.Collections.Generic;
+using System.Globalization;
+using System.IO;
+using System.Reflection;
+using System.Runtime.CompilerServices;
+using System.Text;
+
+namespace DM
+{
+    /// <summary>
+    ///     Implements the <c>/savefile</c> datum: a tree of directories, each of which can hold a value.
+    /// </summary>
+    /// <remarks>
+    ///     Savefiles are stored in BYOND's text savefile format, the same one <c>ExportText()</c> produces,
+    ///     and are written out after every change.
+    ///     Objects are saved as a <c>.0</c>, <c>.1</c>... directory under the value that refers to them,
+    ///     holding their <c>type</c> and either their saved vars or whatever their <c>Write()</c> proc wrote.
+    /// </remarks>
+    public static class DmSavefile
+    {
+        private static readonly ConditionalWeakTable<object, Savefile> Savefiles =
+            new ConditionalWeakTable<object, Savefile>();
+
+        /// <summary>
+        ///     Implements <c>/savefile/New(filename, timeout)</c>. Without a filename the savefile is temporary.
+        /// </summary>
+        public static object New(object src, object filename, object timeout)
+        {
+            var savefile = new Savefile(filename == null ? null : DmFile.FullPath(filename));
+            if (savefile.FilePath != null && File.Exists(savefile.FilePath))
+            {
+                Import(savefile.Root, File.ReadAllText(savefile.FilePath));
+            }
+
+            Savefiles.Remove(src);
+            Savefiles.Add(src, savefile);
+            Set(src, "name", filename == null ? null : DmText.Stringify(filename));
+            Set(src, "cd", "/");
+            Set(src, "dir", new DirList(src));
+            return null;
+        }
+
+        /// <summary>
+        ///     Implements <c>/savefile/ExportText(path, file)</c>: the contents of a directory, default the
+        ///     current one, as text. The text is also written to the file if one is passed.
+        /// </summary>
+        public static object ExportText(object src, object path, object file)
+        {
+            var node = Resolve(src, path, false);
+            var builder = new StringBuilder();
+            if (node != null)
+            {
+                Export(builder, node, 0);
+            }
+
+            var text = builder.ToString();
+            if (file != null)
+            {
+                File.WriteAllText(DmFile.FullPath(file), text);
+            }
+
+            return text;
+        }
+
+        /// <summary>
+        ///     Implements <c>/savefile/ImportText(path, source)</c>, reading text or a file in the format
+        ///     <c>ExportText()</c> produces into a directory, default the current one.
+        /// </summary>
+        public static object ImportText(object src, object path, object source)
+        {
+            var text = source is DmFile ? DmFile.File2Text(source) as string : DmText.Stringify(source);
+            Import(Resolve(src, path, true), text ?? "");
+            GetSavefile(src).Save();
+            return null;
+        }
+
+        public static object Flush(object src)
+        {
+            GetSavefile(src).Save();
+            return null;
+        }
+
+        public static bool IsSavefile(object value)
+        {
+            return value != null && Savefiles.TryGetValue(value, out _);
+        }
+
+        /// <summary>
+        ///     Implements <c>S[key] &lt;&lt; value</c>, and <c>S &lt;&lt; value</c> without a key.
+        /// </summary>
+        internal static void Write(object src, object key, object value)
+        {
+            var node = Resolve(src, null, true);
+            if (key != null)
+            {
+                node = node.GetChild(DmText.Stringify(key), true);
+            }
+
+            new Writer(src).WriteValue(node, value);
+            GetSavefile(src).Save();
+        }
+
+        /// <summary>
+        ///     Implements <c>S[key] &gt;&gt; V</c>, and <c>S &gt;&gt; V</c> without a key.
+        ///     Missing values read as null.
+        /// </summary>
+        internal static object Read(object src, object key)
+        {
+            var node = Resolve(src, null, false);
+            if (node != null && key != null)
+            {
+                node = node.GetChild(DmText.Stringify(key), false);
+            }
+
+            return node == null ? null : ReadValue(src, node, node.Value);
+        }
+
+        private static Savefile GetSavefile(object src)
+        {
+            if (!Savefiles.TryGetValue(src, out var savefile))
+            {
+                throw new InvalidOperationException("The savefile was not opened.");
+            }
+
+            return savefile;
+        }
+
+        /// <summary>
+        ///     Finds a directory by a path relative to <c>cd</c>.
+        ///     Returns null if it doesn't exist and <paramref name="create" /> is false.
+        /// </summary>
+        private static Node Resolve(object src, object path, bool create)
+        {
+            var root = GetSavefile(src).Root;
+            var node = Walk(root, root, Get(src, "cd") as string ?? "/", create);
+            return path == null || node == null ? node : Walk(root, node, DmText.Stringify(path), create);
+        }
+
+        private static Node Walk(Node root, Node from, string path, bool create)
+        {
+            var node = path.StartsWith("/", StringComparison.Ordinal) ? root : from;
+            foreach (var part in path.Split('/'))
+            {
+                if (part.Length == 0 || part == ".")
+                {
+                    continue;
+                }
+
+                node = part == ".." ? node.Parent ?? node : node.GetChild(part, create);
+                if (node == null)
+                {
+                    return null;
+                }
+            }
+
+            return node;
+        }
+
+        private static object ReadValue(object src, Node holder, object value)
+        {
+            switch (value)
+            {
+                case SavedList saved:
+                    var list = new DmList();
+                    foreach (var entry in saved.Entries)
+                    {
+                        var key = ReadValue(src, holder, entry.Key);
+                        if (entry.HasValue)
+                        {
+                            list.SetAssoc(key, ReadValue(src, holder, entry.Value));
+                        }
+                        else
+                        {
+                            list.Append(key);
+                        }
+                    }
+
+                    return list;
+                case ObjectRef reference:
+                    var node = holder.GetChild(reference.Name, false);
+                    return node == null ? null : ReadObject(src, node);
+                default:
+                    return value;
+            }
+        }
+
+        private static object ReadObject(object src, Node node)
+        {
+            var typeValue = node.GetChild("type", false)?.Value;
+            var path = typeValue as DmPath ?? DmPath.FromText(typeValue);
+            if (path == null)
+            {
+                throw new InvalidOperationException($"Savefile object {node.Path} has no valid type.");
+            }
+
+            var obj = Activator.CreateInstance(path.ClrType);
+            var read = GetCodeProc(obj, "Read");
+            if (read != null)
+            {
+                InDirectory(src, node, () => read.Invoke(obj, new object[] {new[] {src}}));
+                return obj;
+            }
+
+            foreach (var child in node.Children)
+            {
+                var field = child.Name == "type" ? null : DmVars.GetField(obj, child.Name);
+                field?.SetValue(obj, ReadValue(src, child, child.Value));
+            }
+
+            return obj;
+        }
+
+        /// <summary>
+        ///     Finds a <c>Write()</c> or <c>Read()</c> proc written in DM, if the object overrides it.
+        /// </summary>
+        private static MethodInfo GetCodeProc(object obj, string name)
+        {
+            return obj.GetType().GetMethod(name, BindingFlags.Public | BindingFlags.Instance, null,
+                new[] {typeof(object[])}, null);
+        }
+
+        /// <summary>
+        ///     Runs <paramref name="action" /> with <c>cd</c> set to a directory, so the procs of the object
+        ///     saved there read and write relative to it.
+        /// </summary>
+        private static void InDirectory(object src, Node node, Action action)
+        {
+            var cd = Get(src, "cd");
+            Set(src, "cd", node.Path);
+            try
+            {
+                action();
+            }
+            finally
+            {
+                Set(src, "cd", cd);
+            }
+        }
+
+        private static void Export(StringBuilder builder, Node node, int depth)
+        {
+            foreach (var child in node.Children)
+            {
+                builder.Append('\t', depth);
+                builder.Append(FormatName(child.Name));
+                if (child.HasValue)
+                {
+                    builder.Append(" = ");
+                    FormatValue(builder, child.Value);
+                }
+
+                builder.Append('\n');
+                Export(builder, child, depth + 1);
+            }
+        }
+
+        private static string FormatName(string name)
+        {
+            foreach (var c in name)
+            {
+                if (char.IsWhiteSpace(c) || c == '=' || c == '"' || c == '\\')
+                {
+                    var builder = new StringBuilder();
+                    FormatString(builder, name);
+                    return builder.ToString();
+                }
+            }
+
+            return name;
+        }
+
+        private static void FormatValue(StringBuilder builder, object value)
+        {
+            switch (value)
+            {
+                case null:
+                    builder.Append("null");
+                    break;
+                case float f:
+                    builder.Append(f.ToString("R", CultureInfo.InvariantCulture));
+                    break;
+                case string s:
+                    FormatString(builder, s);
+                    break;
+                case DmPath path:
+                    builder.Append(path.Path);
+                    break;
+                case ObjectRef reference:
+                    builder.Append("object(");
+                    FormatString(builder, reference.Name);
+                    builder.Append(')');
+                    break;
+                case SavedList list:
+                    builder.Append("list(");
+                    var first = true;
+                    foreach (var entry in list.Entries)
+                    {
+                        if (!first)
+                        {
+                            builder.Append(',');
+                        }
+
+                        first = false;
+                        FormatValue(builder, entry.Key);
+                        if (entry.HasValue)
+                        {
+                            builder.Append(" = ");
+                            FormatValue(builder, entry.Value);
+                        }
+                    }
+
+                    builder.Append(')');
+                    break;
+                default:
+                    throw new InvalidOperationException($"Cannot save {DmText.Stringify(value)}.");
+            }
+        }
+
+        private static void FormatString(StringBuilder builder, string text)
+        {
+            builder.Append('"');
+            foreach (var c in text)
+            {
+                switch (c)
+                {
+                    case '"':
+                        builder.Append("\\\"");
+                        break;
+                    case '\\':
+                        builder.Append("\\\\");
+                        break;
+                    case '\n':
+                        builder.Append("\\n");
+                        break;
+                    case '\t':
+                        builder.Append("\\t");
+                        break;
+                    default:
+                        builder.Append(c);
+                        break;
+                }
+            }
+
+            builder.Append('"');
+        }
+
+        /// <summary>
+        ///     Reads text savefile lines into a directory. Each line is a name, optionally followed by
+        ///     <c>= value</c>, and lines indented one tab further are inside the directory above them.
+        /// </summary>
+        private static void Import(Node into, string text)
+        {
+            var parents = new List<Node> {into};
+            var lineNumber = 0;
+            foreach (var rawLine in text.Split('\n'))
+            {
+                lineNumber++;
+                var line = rawLine.TrimEnd('\r');
+                var depth = 0;
+                while (depth < line.Length && line[depth] == '\t')
+                {
+                    depth++;
+                }
+
+                if (line.Trim().Length == 0)
+                {
+                    continue;
+                }
+
+                if (depth >= parents.Count)
+                {
+                    throw new InvalidOperationException($"Savefile text line {lineNumber} is indented too far.");
+                }
+
+                var parser = new LineParser(line, depth, lineNumber);
+                var node = parents[depth].GetChild(parser.ReadName(), true);
+                if (parser.TryReadEquals())
+                {
+                    node.SetValue(parser.ReadValue());
+                }
+
+                parser.ExpectEnd();
+                parents.RemoveRange(depth + 1, parents.Count - depth - 1);
+                parents.Add(node);
+            }
+        }
+
+        private static object Get(object src, string name)
+        {
+            return DmVars.GetField(src, name)?.GetValue(src);
+        }
+
+        private static void Set(object src, string name, object value)
+        {
+            DmVars.GetField(src, name)?.SetValue(src, value);
+        }
+
+        private sealed class Savefile
+        {
+            public readonly string FilePath;
+            public readonly Node Root = new Node("", null);
+
+            public Savefile(string filePath)
+            {
+                FilePath = filePath;
+            }
+
+            public void Save()
+            {
+                if (FilePath == null)
+                {
+                    return;
+                }
+
+                var builder = new StringBuilder();
+                Export(builder, Root, 0);
+                var directory = System.IO.Path.GetDirectoryName(FilePath);
+                if (!string.IsNullOrEmpty(directory))
+                {
+                    Directory.CreateDirectory(directory);
+                }
+
+                File.WriteAllText(FilePath, builder.ToString());
+            }
+        }
+
+        private sealed class Node
+        {
+            private readonly List<Node> _children = new List<Node>();
+            private readonly Dictionary<string, Node> _byName = new Dictionary<string, Node>();
+
+            public readonly string Name;
+            public readonly Node Parent;
+
+            public Node(string name, Node parent)
+            {
+                Name = name;
+                Parent = parent;
+            }
+
+            public bool HasValue { get; private set; }
+            public object Value { get; private set; }
+            public IEnumerable<Node> Children => _children;
+
+            public string Path
+            {
+                get
+                {
+                    if (Parent == null)
+                    {
+                        return "/";
+                    }
+
+                    return Parent.Parent == null ? "/" + Name : Parent.Path + "/" + Name;
+                }
+            }
+
+            public Node GetChild(string name, bool create)
+            {
+                if (_byName.TryGetValue(name, out var child) || !create)
+                {
+                    return child;
+                }
+
+                child = new Node(name, this);
+                _children.Add(child);
+                _byName.Add(name, child);
+                return child;
+            }
+
+            public void SetValue(object value)
+            {
+                Value = value;
+                HasValue = true;
+            }
+
+            public void Clear()
+            {
+                _children.Clear();
+                _byName.Clear();
+                Value = null;
+                HasValue = false;
+            }
+
+            /// <summary>
+            ///     The name for the next object saved in this directory.
+            /// </summary>
+            public string NextObjectName()
+            {
+                var count = 0;
+                while (_byName.ContainsKey("." + count))
+                {
+                    count++;
+                }
+
+                return "." + count;
+            }
+        }
+
+        /// <summary>
+        ///     A reference to an object saved in a directory, written as <c>object(".0")</c>.
+        /// </summary>
+        private sealed class ObjectRef
+        {
+            public readonly string Name;
+
+            public ObjectRef(string name)
+            {
+                Name = name;
+            }
+        }
+
+        /// <summary>
+        ///     A saved list, written as <c>list("a", "b" = 1)</c>.
+        /// </summary>
+        private sealed class SavedList
+        {
+            public readonly List<Entry> Entries = new List<Entry>();
+
+            public sealed class Entry
+            {
+                public readonly bool HasValue;
+                public readonly object Key;
+                public readonly object Value;
+
+                public Entry(object key, bool hasValue, object value)
+                {
+                    Key = key;
+                    HasValue = hasValue;
+                    Value = value;
+                }
+            }
+        }
+
+        /// <summary>
+        ///     Turns values into what gets stored, saving objects into directories along the way.
+        /// </summary>
+        private sealed class Writer
+        {
+            private readonly object _savefile;
+            private readonly HashSet<object> _writing = new HashSet<object>();
+
+            public Writer(object savefile)
+            {
+                _savefile = savefile;
+            }
+
+            public void WriteValue(Node node, object value)
+            {
+                node.Clear();
+                node.SetValue(Convert(node, value));
+            }
+
+            private object Convert(Node holder, object value)
+            {
+                switch (value)
+                {
+                    case null:
+                    case string _:
+                    case DmPath _:
+                        return value;
+                    case float _:
+                    case int _:
+                    case double _:
+                        return (float) DmMath.ToNumber(value, "value");
+                    case DmFile file:
+                        return file.Path;
+                    case DmList list:
+                        var saved = new SavedList();
+                        foreach (var key in list)
+                        {
+                            var assoc = list.GetAssoc(key);
+                            saved.Entries.Add(new SavedList.Entry(Convert(holder, key), assoc != null,
+                                Convert(holder, assoc)));
+                        }
+
+                        return saved;
+                }
+
+                if (DmPath.Of(value) == null)
+                {
+                    throw new InvalidOperationException($"Cannot save {DmText.Stringify(value)}.");
+                }
+
+                if (!_writing.Add(value))
+                {
+                    throw new InvalidOperationException("Cannot save an object that contains itself.");
+                }
+
+                var node = holder.GetChild(holder.NextObjectName(), true);
+                WriteObject(node, value);
+                _writing.Remove(value);
+                return new ObjectRef(node.Name);
+            }
+
+            private void WriteObject(Node node, object obj)
+            {
+                var path = DmPath.Of(obj);
+                node.GetChild("type", true).SetValue(path);
+
+                var write = GetCodeProc(obj, "Write");
+                if (write != null)
+                {
+                    InDirectory(_savefile, node, () => write.Invoke(obj, new object[] {new[] {_savefile}}));
+                    return;
+                }
+
+                foreach (var field in obj.GetType().GetFields(BindingFlags.Public | BindingFlags.Instance))
+                {
+                    var info = path.GetVar(field.Name);
+                    var value = field.GetValue(obj);
+                    // Like BYOND, only vars changed from their initial value get saved.
+                    if (info == null || !info.IsSaved || Equals(value, info.Initial))
+                    {
+                        continue;
+                    }
+
+                    WriteValue(node.GetChild(field.Name, true), value);
+                }
+            }
+        }
+
+        /// <summary>
+        ///     Parses one line of a text savefile.
+        /// </summary>
+        private sealed class LineParser
+        {
+            private readonly int _lineNumber;
+            private readonly string _text;
+            private int _pos;
+
+            public LineParser(string text, int start, int lineNumber)
+            {
+                _text = text;
+                _pos = start;
+                _lineNumber = lineNumber;
+            }
+
+            public string ReadName()
+            {
+                if (Peek() == '"')
+                {
+                    return ReadString();
+                }
+
+                var start = _pos;
+                while (_pos < _text.Length && _text[_pos] != '=' && !char.IsWhiteSpace(_text[_pos]))
+                {
+                    _pos++;
+                }
+
+                return _text.Substring(start, _pos - start);
+            }
+
+            public bool TryReadEquals()
+            {
+                SkipSpaces();
+                if (Peek() != '=')
+                {
+                    return false;
+                }
+
+                _pos++;
+                return true;
+            }
+
+            public object ReadValue()
+            {
+                SkipSpaces();
+                var c = Peek();
+                if (c == '"')
+                {
+                    return ReadString();
+                }
+
+                if (c == '/')
+                {
+                    var path = ReadWord();
+                    return DmPath.FromText(path) ?? throw Error($"Unknown type {path}");
+                }
+
+                if (c == '-' || c == '+' || c == '.' || char.IsDigit(c))
+                {
+                    var number = ReadWord();
+                    if (!float.TryParse(number, NumberStyles.Float, CultureInfo.InvariantCulture, out var value))
+                    {
+                        throw Error($"Invalid number {number}");
+                    }
+
+                    return value;
+                }
+
+                var word = ReadWord();
+                switch (word)
+                {
+                    case "null":
+                        return null;
+                    case "list":
+                        return ReadList();
+                    case "object":
+                        Expect('(');
+                        SkipSpaces();
+                        var name = ReadString();
+                        Expect(')');
+                        return new ObjectRef(name);
+                    case "filedata":
+                        throw Error("File data in savefiles is not supported");
+                    default:
+                        throw Error($"Unexpected {word}");
+                }
+            }
+
+            public void ExpectEnd()
+            {
+                SkipSpaces();
+                if (_pos < _text.Length)
+                {
+                    throw Error("Unexpected text at the end of the line");
+                }
+            }
+
+            private SavedList ReadList()
+            {
+                var list = new SavedList();
+                Expect('(');
+                SkipSpaces();
+                if (Peek() == ')')
+                {
+                    _pos++;
+                    return list;
+                }
+
+                while (true)
+                {
+                    var key = ReadValue();
+                    var hasValue = TryReadEquals();
+                    list.Entries.Add(new SavedList.Entry(key, hasValue, hasValue ? ReadValue() : null));
+                    SkipSpaces();
+                    var c = Peek();
+                    _pos++;
+                    if (c == ')')
+                    {
+                        return list;
+                    }
+
+                    if (c != ',')
+                    {
+                        throw Error("Expected ',' or ')'");
+                    }
+                }
+            }
+
+            private string ReadString()
+            {
+                Expect('"');
+                var builder = new StringBuilder();
+                while (true)
+                {
+                    if (_pos >= _text.Length)
+                    {
+                        throw Error("Unterminated text");
+                    }
+
+                    var c = _text[_pos++];
+                    if (c == '"')
+                    {
+                        return builder.ToString();
+                    }
+
+                    if (c == '\\' && _pos < _text.Length)
+                    {
+                        c = _text[_pos++];
+                        c = c == 'n' ? '\n' : c == 't' ? '\t' : c;
+                    }
+
+                    builder.Append(c);
+                }
+            }
+
+            /// <summary>
+            ///     Reads up to the next space or punctuation that ends a value.
+            /// </summary>
+            private string ReadWord()
+            {
+                var start = _pos;
+                while (_pos < _text.Length && "(),= \t".IndexOf(_text[_pos]) == -1)
+                {
+                    _pos++;
+                }
+
+                return _text.Substring(start, _pos - start);
+            }
+
+            private void Expect(char c)
+            {
+                if (Peek() != c)
+                {
+                    throw Error($"Expected '{c}'");
+                }
+
+                _pos++;
+            }
+
+            private char Peek()
+            {
+                return _pos < _text.Length ? _text[_pos] : '\0';
+            }
+
+            private void SkipSpaces()
+            {
+                while (_pos < _text.Length && (_text[_pos] == ' ' || _text[_pos] == '\t'))
+                {
+                    _pos++;
+                }
+            }
+
+            private Exception Error(string message)
+            {
+                return new InvalidOperationException($"Savefile text error: {message} on line {_lineNumber}.");
+            }
+        }
+
+        /// <summary>
+        ///     The <c>dir</c> var: the names in the current directory, kept up to date with <c>cd</c>.
+        /// </summary>
+        private sealed class DirList : DmList
+        {
+            private readonly object _savefile;
+
+            public DirList(object savefile)
+            {
+                _savefile = savefile;
+            }
+
+            protected override void Refresh()
+            {
+                var names = new List<object>();
+                var node = Resolve(_savefile, null, false);
+                if (node != null)
+                {
+                    foreach (var child in node.Children)
+                    {
+                        names.Add(child.Name);
+                    }
+                }
+
+                SetItems(names);
+            }
+        }
+    }
+}
@@ -0,0 +1,382 @@
+using System;
+using System.Collections.Concurrent;
+using System.Collections.Generic;
+using System.Diagnostics;
+using System.Threading;
+using System.Threading.Tasks;
+
+namespace DM
+{
+    /// <summary>
+    ///     Runs DM code so that procs can sleep.
+    /// </summary>
+    /// <remarks>
+    ///     Every DM "thread" (a call chain that can sleep independently) is backed by an OS thread,
+    ///     but only one of them ever runs at a time. Control is handed back and forth with semaphores,
+    ///     so DM code never has to deal with actual concurrency.
+    /// </remarks>
+    public static class DmScheduler
+    {
+        private static readonly List<Sleeper> Sleeping = new List<Sleeper>();
+
+        /// <summary>
+        ///     DM threads to start, handed over from other OS threads, like the Topic server.
+        /// </summary>
+        private static readonly ConcurrentQueue<Action> Posted = new ConcurrentQueue<Action>();
+
+        /// <summary>
+        ///     DM threads whose task in <see cref="WaitFor{T}" /> is done, with the run they belong to.
+        /// </summary>
+        private static readonly ConcurrentQueue<(DmThread, int)> Woken = new ConcurrentQueue<(DmThread, int)>();
+
+        private static long _sleepSequence;
+
+        /// <summary>
+        ///     How many DM threads are in <see cref="WaitFor{T}" />, which keeps the program running.
+        /// </summary>
+        private static int _waiting;
+
+        /// <summary>
+        ///     Counts calls to <see cref="Run" />, so threads abandoned by a stopped run aren't woken by the next.
+        /// </summary>
+        private static int _run;
+
+        /// <summary>
+        ///     Set when the world is shut down, to stop running DM code.
+        /// </summary>
+        private static bool _stopping;
+
+        /// <summary>
+        ///     Cancelled when the current call to <see cref="Run" /> returns, so OS threads waiting on posted
+        ///     DM code give up instead of waiting for a run that's over.
+        /// </summary>
+        private static CancellationTokenSource _runEnded = new CancellationTokenSource();
+
+        [ThreadStatic] private static DmThread _current;
+
+        /// <summary>
+        ///     How many ticks have passed since the program started.
+        /// </summary>
+        public static long Ticks { get; private set; }
+
+        /// <summary>
+        ///     How much time has passed since the program started, in deciseconds. Only advances between ticks.
+        /// </summary>
+        public static double Time { get; private set; }
+
+        /// <summary>
+        ///     The length of a tick, in deciseconds. Set through <c>world.tick_lag</c> and <c>world.fps</c>.
+        /// </summary>
+        public static float TickLag { get; set; } = 1;
+
+        /// <summary>
+        ///     Runs the main proc of the program, and then keeps ticking until nothing is sleeping anymore,
+        ///     or until <see cref="Stop" /> is called.
+        /// </summary>
+        public static void Run(Func<object[], object> main)
+        {
+            _stopping = false;
+            _run++;
+            try
+            {
+                RunThread(Spawn(() => main(new object[0])));
+
+                var tickTimer = Stopwatch.StartNew();
+                while (true)
+                {
+                    RunPosted();
+                    RunDue();
+
+                    if (_stopping)
+                    {
+                        // Whatever was still sleeping never wakes up.
+                        Sleeping.Clear();
+                        _waiting = 0;
+                        return;
+                    }
+
+                    // An open Topic port keeps the world running, waiting for calls.
+                    if (Sleeping.Count == 0 && _waiting == 0 && !DmTopic.IsListening)
+                    {
+                        return;
+                    }
+
+                    DmWorld.UpdateTickRate();
+
+                    // Wait out the rest of the tick in real time.
+                    var remaining = (long) (TickLag * 100) - tickTimer.ElapsedMilliseconds;
+                    if (remaining > 0)
+                    {
+                        Thread.Sleep((int) remaining);
+                    }
+
+                    tickTimer.Restart();
+                    Ticks += 1;
+                    Time += TickLag;
+                    DmWorld.UpdateTime();
+                }
+            }
+            finally
+            {
+                // Posted work that didn't get to run never will.
+                while (Posted.TryDequeue(out _))
+                {
+                }
+
+                Interlocked.Exchange(ref _runEnded, new CancellationTokenSource()).Cancel();
+            }
+        }
+
+        /// <summary>
+        ///     Implements <c>sleep(Delay)</c>. Suspends the current DM thread, letting whatever ran it continue.
+        /// </summary>
+        public static object Sleep(object delay)
+        {
+            var current = _current;
+            if (current == null)
+            {
+                throw new InvalidOperationException("Cannot sleep outside of a proc.");
+            }
+
+            var deciseconds = delay is float f ? f : 0;
+            var wakeTick = Ticks;
+            if (deciseconds > 0)
+            {
+                wakeTick += Math.Max(1, (long) Math.Ceiling(deciseconds / TickLag));
+            }
+
+            SleepUntil(current, wakeTick);
+            return null;
+        }
+
+        /// <summary>
+        ///     Runs a <c>set waitfor = 0</c> proc body on a new DM thread.
+        ///     Returns its result if it finished without sleeping, null otherwise.
+        /// </summary>
+        public static object CallDetached(Func<object[], object> body, object[] args)
+        {
+            object result = null;
+            var thread = Spawn(() => result = body(args));
+            RunThread(thread);
+            if (_stopping)
+            {
+                Halt();
+            }
+
+            return thread.Finished ? result : null;
+        }
+
+        /// <summary>
+        ///     Suspends the current DM thread until <paramref name="task" /> is done, letting the others run,
+        ///     for things like <c>world.Export()</c> that take a while. Exceptions of the task are rethrown.
+        /// </summary>
+        internal static T WaitFor<T>(Task<T> task)
+        {
+            var current = _current;
+            if (current == null || task.IsCompleted)
+            {
+                return task.GetAwaiter().GetResult();
+            }
+
+            // The scheduler can't look at the queue before this thread yields, so this can't wake it early.
+            var run = _run;
+            _waiting++;
+            task.ContinueWith(_ => Woken.Enqueue((current, run)));
+            current.Yield.Release();
+            current.Resume.Wait();
+            return task.GetAwaiter().GetResult();
+        }
+
+        /// <summary>
+        ///     Runs <paramref name="body" /> as a new DM thread, at the start of the next tick.
+        ///     Safe to call from any OS thread. If the run ends first, it's dropped.
+        ///     Returns a token that's cancelled when the run ends, to stop waiting for <paramref name="body" />.
+        /// </summary>
+        internal static CancellationToken Post(Action body)
+        {
+            var runEnded = _runEnded.Token;
+            Posted.Enqueue(body);
+            return runEnded;
+        }
+
+        /// <summary>
+        ///     Stops running DM code, for <c>world.Reboot()</c> and <c>del(world)</c>.
+        ///     The current DM thread never continues, <see cref="Run" /> returns once it's given back control.
+        /// </summary>
+        internal static void Stop()
+        {
+            _stopping = true;
+            Halt();
+        }
+
+        /// <summary>
+        ///     Called in the loops of <c>set background = 1</c> procs.
+        ///     If the current DM thread has been running for longer than a tick, sleeps until the next one.
+        ///     <c>sleep(0)</c> would be resumed in this tick right away, so nothing else would get to run.
+        /// </summary>
+        public static void Background()
+        {
+            var current = _current;
+            if (current != null && current.Slice.ElapsedMilliseconds > TickLag * 100)
+            {
+                SleepUntil(current, Ticks + 1);
+            }
+        }
+
+        /// <summary>
+        ///     Suspends <paramref name="current" /> until the scheduler gets to <paramref name="wakeTick" />.
+        /// </summary>
+        private static void SleepUntil(DmThread current, long wakeTick)
+        {
+            Sleeping.Add(new Sleeper(wakeTick, _sleepSequence++, current));
+            current.Yield.Release();
+            current.Resume.Wait();
+        }
+
+        /// <summary>
+        ///     Suspends the current DM thread for good, if there is one.
+        /// </summary>
+        private static void Halt()
+        {
+            var current = _current;
+            if (current == null)
+            {
+                return;
+            }
+
+            current.Yield.Release();
+            current.Resume.Wait();
+        }
+
+        /// <summary>
+        ///     Runs what other OS threads handed over since last tick.
+        /// </summary>
+        private static void RunPosted()
+        {
+            while (!_stopping && Woken.TryDequeue(out var woken))
+            {
+                var (thread, run) = woken;
+                if (run == _run)
+                {
+                    _waiting--;
+                    RunThread(thread);
+                }
+            }
+
+            while (!_stopping && Posted.TryDequeue(out var body))
+            {
+                RunThread(Spawn(body));
+            }
+        }
+
+        /// <summary>
+        ///     Resumes sleeping threads whose time has come, in the order they went to sleep.
+        /// </summary>
+        private static void RunDue()
+        {
+            while (!_stopping)
+            {
+                var next = -1;
+                for (var i = 0; i < Sleeping.Count; i++)
+                {
+                    var sleeper = Sleeping[i];
+                    if (sleeper.WakeTick > Ticks)
+                    {
+                        continue;
+                    }
+
+                    if (next == -1 || sleeper.CompareTo(Sleeping[next]) < 0)
+                    {
+                        next = i;
+                    }
+                }
+
+                if (next == -1)
+                {
+                    return;
+                }
+
+                var thread = Sleeping[next].Thread;
+                Sleeping.RemoveAt(next);
+                RunThread(thread);
+            }
+        }
+
+        private static DmThread Spawn(Action body)
+        {
+            var thread = new DmThread();
+            var osThread = new Thread(() =>
+            {
+                _current = thread;
+                thread.Resume.Wait();
+                try
+                {
+                    body();
+                }
+                catch (Exception e)
+                {
+                    // Only this DM thread is aborted, everything else keeps running.
+                    DmError.Report(e);
+                }
+                finally
+                {
+                    thread.Finished = true;
+                    thread.Yield.Release();
+                }
+            });
+            osThread.IsBackground = true;
+            osThread.Start();
+            return thread;
+        }
+
+        /// <summary>
+        ///     Lets a DM thread run until it sleeps or finishes.
+        /// </summary>
+        private static void RunThread(DmThread thread)
+        {
+            thread.Slice.Restart();
+            thread.Resume.Release();
+            thread.Yield.Wait();
+        }
+
+        private sealed class DmThread
+        {
+            /// <summary>
+            ///     Released to let this thread run.
+            /// </summary>
+            public readonly SemaphoreSlim Resume = new SemaphoreSlim(0);
+
+            /// <summary>
+            ///     Released by this thread when it sleeps or finishes, to hand control back.
+            /// </summary>
+            public readonly SemaphoreSlim Yield = new SemaphoreSlim(0);
+
+            /// <summary>
+            ///     How long the thread has been running since it was last resumed.
+            /// </summary>
+            public readonly Stopwatch Slice = new Stopwatch();
+
+            public bool Finished;
+        }
+
+        private struct Sleeper : IComparable<Sleeper>
+        {
+            public readonly long WakeTick;
+            public readonly long Sequence;
+            public readonly DmThread Thread;
+
+            public Sleeper(long wakeTick, long sequence, DmThread thread)
+            {
+                WakeTick = wakeTick;
+                Sequence = sequence;
+                Thread = thread;
+            }
+
+            public int CompareTo(Sleeper other)
+            {
+                var cmp = WakeTick.CompareTo(other.WakeTick);
+                return cmp != 0 ? cmp : Sequence.CompareTo(other.Sequence);
+            }
+        }
+    }
+}
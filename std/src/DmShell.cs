@@ -0,0 +1,127 @@
+using System.ComponentModel;
+using System.Diagnostics;
+using System.Runtime.InteropServices;
+using System.Text;
+using System.Threading.Tasks;
+
+namespace DM
+{
+    /// <summary>
+    ///     Running other programs. Only allowed when the program is run with <c>-trusted</c>, like DreamDaemon.
+    /// </summary>
+    /// <remarks>
+    ///     The calling proc sleeps until the command is done, other procs keep running in the meantime.
+    /// </remarks>
+    public static class DmShell
+    {
+        /// <summary>
+        ///     Implements <c>shell(command)</c>: runs the command with the system shell and returns its exit code,
+        ///     or null if it couldn't be run.
+        /// </summary>
+        public static object Shell(object command)
+        {
+            return Run(command)?.ExitCode;
+        }
+
+        /// <summary>
+        ///     Implements <c>world.shelleo(command)</c>, which is <c>shell()</c> returning
+        ///     <c>list(errorlevel, stdout, stderr)</c>.
+        /// </summary>
+        public static object ShellEo(object src, object command)
+        {
+            var result = Run(command);
+            var list = new DmList();
+            list.Append(result?.ExitCode);
+            list.Append(result?.Output);
+            list.Append(result?.Error);
+            return list;
+        }
+
+        private static Result Run(object command)
+        {
+            if (!DmWorld.Trusted)
+            {
+                throw new DmRuntimeException("shell() is not allowed, run the program with -trusted to allow it");
+            }
+
+            var windows = RuntimeInformation.IsOSPlatform(OSPlatform.Windows);
+            var text = DmText.Stringify(command);
+            var info = new ProcessStartInfo
+            {
+                FileName = windows ? "cmd.exe" : "/bin/sh",
+                // cmd.exe takes the rest of its command line as is, sh needs it as one argument.
+                Arguments = windows ? "/c " + text : "-c " + QuoteArgument(text),
+                UseShellExecute = false,
+                RedirectStandardOutput = true,
+                RedirectStandardError = true
+            };
+
+            try
+            {
+                return DmScheduler.WaitFor(Task.Run(async () =>
+                {
+                    using (var process = Process.Start(info))
+                    {
+                        var output = process.StandardOutput.ReadToEndAsync();
+                        var error = process.StandardError.ReadToEndAsync();
+                        process.WaitForExit();
+                        return new Result(process.ExitCode, await output, await error);
+                    }
+                }));
+            }
+            catch (Win32Exception)
+            {
+                // No shell to run it with.
+                return null;
+            }
+        }
+
+        /// <summary>
+        ///     Quotes an argument so that it survives the way .NET splits up <see cref="ProcessStartInfo.Arguments" />:
+        ///     backslashes only escape quotes and other backslashes in front of quotes.
+        /// </summary>
+        private static string QuoteArgument(string argument)
+        {
+            var builder = new StringBuilder("\"");
+            var backslashes = 0;
+            foreach (var c in argument)
+            {
+                if (c == '\\')
+                {
+                    backslashes++;
+                    continue;
+                }
+
+                if (c == '"')
+                {
+                    builder.Append('\\', backslashes * 2 + 1);
+                }
+                else
+                {
+                    builder.Append('\\', backslashes);
+                }
+
+                backslashes = 0;
+                builder.Append(c);
+            }
+
+            builder.Append('\\', backslashes * 2);
+            builder.Append('"');
+            return builder.ToString();
+        }
+
+        private sealed class Result
+        {
+            public readonly object ExitCode;
+            public readonly string Output;
+            public readonly string Error;
+
+            public Result(int exitCode, string output, string error)
+            {
+                ExitCode = (float) exitCode;
+                Output = output;
+                Error = error;
+            }
+        }
+    }
+}
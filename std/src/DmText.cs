@@ -0,0 +1,786 @@
+using System;
+using System.Collections.Generic;
+using System.Globalization;
+using System.Text;
+
+namespace DM
+{
+    /// <summary>
+    ///     The text builtins.
+    /// </summary>
+    /// <remarks>
+    ///     DM text is UTF-8, and positions count bytes unless you use the <c>_char</c> version of a proc,
+    ///     which counts characters instead. Positions start at 1, and 0 or a negative one counts from the end.
+    /// </remarks>
+    public static class DmText
+    {
+        private const string Digits = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+        public static object Length(object e)
+        {
+            return Length(e, false);
+        }
+
+        public static object LengthChar(object e)
+        {
+            return Length(e, true);
+        }
+
+        public static object CopyText(object t, object start, object end)
+        {
+            return CopyText(t, start, end, false);
+        }
+
+        public static object CopyTextChar(object t, object start, object end)
+        {
+            return CopyText(t, start, end, true);
+        }
+
+        public static object FindText(object haystack, object needle, object start, object end)
+        {
+            return FindText(haystack, needle, start, end, StringComparison.OrdinalIgnoreCase, false);
+        }
+
+        public static object FindTextChar(object haystack, object needle, object start, object end)
+        {
+            return FindText(haystack, needle, start, end, StringComparison.OrdinalIgnoreCase, true);
+        }
+
+        public static object FindTextEx(object haystack, object needle, object start, object end)
+        {
+            return FindText(haystack, needle, start, end, StringComparison.Ordinal, false);
+        }
+
+        public static object FindTextExChar(object haystack, object needle, object start, object end)
+        {
+            return FindText(haystack, needle, start, end, StringComparison.Ordinal, true);
+        }
+
+        public static object FindLastText(object haystack, object needle, object start, object end)
+        {
+            return FindLastText(haystack, needle, start, end, StringComparison.OrdinalIgnoreCase, false);
+        }
+
+        public static object FindLastTextChar(object haystack, object needle, object start, object end)
+        {
+            return FindLastText(haystack, needle, start, end, StringComparison.OrdinalIgnoreCase, true);
+        }
+
+        public static object FindLastTextEx(object haystack, object needle, object start, object end)
+        {
+            return FindLastText(haystack, needle, start, end, StringComparison.Ordinal, false);
+        }
+
+        public static object FindLastTextExChar(object haystack, object needle, object start, object end)
+        {
+            return FindLastText(haystack, needle, start, end, StringComparison.Ordinal, true);
+        }
+
+        public static object ReplaceText(object haystack, object needle, object replacement, object start,
+            object end)
+        {
+            return ReplaceText(haystack, needle, replacement, start, end, StringComparison.OrdinalIgnoreCase,
+                false);
+        }
+
+        public static object ReplaceTextChar(object haystack, object needle, object replacement, object start,
+            object end)
+        {
+            return ReplaceText(haystack, needle, replacement, start, end, StringComparison.OrdinalIgnoreCase,
+                true);
+        }
+
+        public static object ReplaceTextEx(object haystack, object needle, object replacement, object start,
+            object end)
+        {
+            return ReplaceText(haystack, needle, replacement, start, end, StringComparison.Ordinal, false);
+        }
+
+        public static object ReplaceTextExChar(object haystack, object needle, object replacement, object start,
+            object end)
+        {
+            return ReplaceText(haystack, needle, replacement, start, end, StringComparison.Ordinal, true);
+        }
+
+        public static object SplitText(object text, object delimiter, object start, object end,
+            object includeDelimiters)
+        {
+            return SplitText(text, delimiter, start, end, includeDelimiters, false);
+        }
+
+        public static object SplitTextChar(object text, object delimiter, object start, object end,
+            object includeDelimiters)
+        {
+            return SplitText(text, delimiter, start, end, includeDelimiters, true);
+        }
+
+        public static object Text2Ascii(object t, object pos)
+        {
+            return Text2Ascii(t, pos, false);
+        }
+
+        public static object Text2AsciiChar(object t, object pos)
+        {
+            return Text2Ascii(t, pos, true);
+        }
+
+        public static object SpanText(object haystack, object needles, object start)
+        {
+            return SpanText(haystack, needles, start, true, false);
+        }
+
+        public static object SpanTextChar(object haystack, object needles, object start)
+        {
+            return SpanText(haystack, needles, start, true, true);
+        }
+
+        public static object NonSpanText(object haystack, object needles, object start)
+        {
+            return SpanText(haystack, needles, start, false, false);
+        }
+
+        public static object NonSpanTextChar(object haystack, object needles, object start)
+        {
+            return SpanText(haystack, needles, start, false, true);
+        }
+
+        /// <summary>
+        ///     Implements <c>jointext(List, Glue, Start = 1, End = 0)</c>.
+        /// </summary>
+        public static object JoinText(object list, object glue, object start, object end)
+        {
+            if (!(list is DmList dmList))
+            {
+                return Stringify(list);
+            }
+
+            var from = ResolvePosition(start, 1, dmList.Count);
+            var to = ResolvePosition(end, 0, dmList.Count);
+            var glueText = Stringify(glue);
+
+            var builder = new StringBuilder();
+            for (var i = from; i < to; i++)
+            {
+                if (i != from)
+                {
+                    builder.Append(glueText);
+                }
+
+                builder.Append(Stringify(dmList[i]));
+            }
+
+            return builder.ToString();
+        }
+
+        public static object UpperText(object t)
+        {
+            return t is string s ? s.ToUpperInvariant() : t;
+        }
+
+        public static object LowerText(object t)
+        {
+            return t is string s ? s.ToLowerInvariant() : t;
+        }
+
+        /// <summary>
+        ///     Implements <c>ascii2text(N)</c>, which takes any Unicode code point despite the name.
+        /// </summary>
+        public static object Ascii2Text(object n)
+        {
+            var codePoint = ToInt(n, 0);
+            if (codePoint <= 0 || codePoint > 0x10FFFF || (codePoint >= 0xD800 && codePoint <= 0xDFFF))
+            {
+                return "";
+            }
+
+            return char.ConvertFromUtf32(codePoint);
+        }
+
+        public static object TrimText(object text)
+        {
+            return text is string s ? s.Trim() : text;
+        }
+
+        /// <summary>
+        ///     Implements <c>cmptext(T1, T2, ...)</c>: whether all the arguments are the same text, ignoring case.
+        /// </summary>
+        public static object CmpText(object[] args)
+        {
+            for (var i = 1; i < args.Length; i++)
+            {
+                if (!string.Equals(Stringify(args[0]), Stringify(args[i]), StringComparison.OrdinalIgnoreCase))
+                {
+                    return DmInternal.Bool(false);
+                }
+            }
+
+            return DmInternal.Bool(true);
+        }
+
+        /// <summary>
+        ///     Implements <c>sorttext(T1, T2, ...)</c>, ignoring case.
+        ///     1 if the arguments are in ascending order, -1 if they are in descending order, 0 otherwise.
+        /// </summary>
+        public static object SortText(object[] args)
+        {
+            var ascending = true;
+            var descending = true;
+            for (var i = 1; i < args.Length; i++)
+            {
+                var cmp = string.Compare(Stringify(args[i - 1]), Stringify(args[i]),
+                    StringComparison.OrdinalIgnoreCase);
+                ascending &= cmp < 0;
+                descending &= cmp > 0;
+            }
+
+            if (args.Length < 2 || ascending == descending)
+            {
+                return 0f;
+            }
+
+            return ascending ? 1f : -1f;
+        }
+
+        /// <summary>
+        ///     Implements <c>num2text(N, Digits = 6, Radix = 10)</c>.
+        ///     With a radix, <c>Digits</c> is the minimum amount of digits instead of the significant ones.
+        /// </summary>
+        public static object Num2Text(object n, object digits, object radix)
+        {
+            if (!IsNumber(n))
+            {
+                return Stringify(n);
+            }
+
+            var number = Convert.ToSingle(n);
+            var radixValue = ToInt(radix, 10);
+            if (radixValue == 10 || radixValue < 2 || radixValue > 36)
+            {
+                return FormatNumber(number, Math.Max(1, ToInt(digits, 6)));
+            }
+
+            var value = (long) Math.Abs(number);
+            var builder = new StringBuilder();
+            do
+            {
+                builder.Insert(0, Digits[(int) (value % radixValue)]);
+                value /= radixValue;
+            } while (value != 0);
+
+            while (builder.Length < ToInt(digits, 0))
+            {
+                builder.Insert(0, '0');
+            }
+
+            if (number < 0)
+            {
+                builder.Insert(0, '-');
+            }
+
+            return builder.ToString();
+        }
+
+        /// <summary>
+        ///     Implements <c>text2num(T, Radix = 10)</c>.
+        ///     Reads as much of a number as there is at the start of the text, null if there is none.
+        /// </summary>
+        public static object Text2Num(object t, object radix)
+        {
+            if (IsNumber(t))
+            {
+                return t;
+            }
+
+            if (!(t is string s))
+            {
+                return null;
+            }
+
+            var text = s.TrimStart();
+            var radixValue = ToInt(radix, 10);
+            if (radixValue != 10 && radixValue >= 2 && radixValue <= 36)
+            {
+                return ParseInteger(text, radixValue);
+            }
+
+            // Sign, digits, fraction, exponent.
+            var end = 0;
+            if (end < text.Length && (text[end] == '-' || text[end] == '+'))
+            {
+                end++;
+            }
+
+            var digitsStart = end;
+            while (end < text.Length && char.IsDigit(text[end]))
+            {
+                end++;
+            }
+
+            if (end < text.Length && text[end] == '.')
+            {
+                end++;
+                while (end < text.Length && char.IsDigit(text[end]))
+                {
+                    end++;
+                }
+            }
+
+            if (end == digitsStart || (end == digitsStart + 1 && text[digitsStart] == '.'))
+            {
+                return null;
+            }
+
+            if (end < text.Length && (text[end] == 'e' || text[end] == 'E'))
+            {
+                var exponentEnd = end + 1;
+                if (exponentEnd < text.Length && (text[exponentEnd] == '-' || text[exponentEnd] == '+'))
+                {
+                    exponentEnd++;
+                }
+
+                var exponentDigits = exponentEnd;
+                while (exponentEnd < text.Length && char.IsDigit(text[exponentEnd]))
+                {
+                    exponentEnd++;
+                }
+
+                if (exponentEnd != exponentDigits)
+                {
+                    end = exponentEnd;
+                }
+            }
+
+            return float.Parse(text.Substring(0, end), NumberStyles.Float, CultureInfo.InvariantCulture);
+        }
+
+        /// <summary>
+        ///     Turns any value into text the way DM does when printing it or embedding it in text.
+        /// </summary>
+        public static string Stringify(object value)
+        {
+            switch (value)
+            {
+                case null:
+                    return "";
+                case string s:
+                    return s;
+                case float f:
+                    return FormatNumber(f, 6);
+                case int i:
+                    return FormatNumber(i, 6);
+                case double d:
+                    return FormatNumber((float) d, 6);
+                case DmList _:
+                    return "/list";
+                default:
+                    return value.ToString();
+            }
+        }
+
+        /// <summary>
+        ///     Formats a number like BYOND, which is C's <c>%g</c> with a three digit exponent:
+        ///     <c>1e+007</c>, <c>0.001</c>, <c>123457</c>.
+        /// </summary>
+        private static string FormatNumber(float number, int digits)
+        {
+            if (float.IsNaN(number))
+            {
+                return "nan";
+            }
+
+            if (float.IsInfinity(number))
+            {
+                return number > 0 ? "inf" : "-inf";
+            }
+
+            if (number == 0)
+            {
+                return "0";
+            }
+
+            // Round to the significant digits first, the exponent can change from that.
+            var scientific = ((double) number).ToString("E" + (digits - 1), CultureInfo.InvariantCulture);
+            var exponentIndex = scientific.IndexOf('E');
+            var exponent = int.Parse(scientific.Substring(exponentIndex + 1), CultureInfo.InvariantCulture);
+
+            if (exponent < -4 || exponent >= digits)
+            {
+                var mantissa = TrimFraction(scientific.Substring(0, exponentIndex));
+                return $"{mantissa}e{(exponent < 0 ? '-' : '+')}{Math.Abs(exponent):000}";
+            }
+
+            var fixedPoint = ((double) number).ToString("F" + Math.Max(0, digits - 1 - exponent),
+                CultureInfo.InvariantCulture);
+            return TrimFraction(fixedPoint);
+        }
+
+        /// <summary>
+        ///     Removes trailing zeros after the decimal point, and the point itself if nothing is left after it.
+        /// </summary>
+        private static string TrimFraction(string number)
+        {
+            if (number.IndexOf('.') == -1)
+            {
+                return number;
+            }
+
+            return number.TrimEnd('0').TrimEnd('.');
+        }
+
+        private static object ParseInteger(string text, int radix)
+        {
+            var negative = text.StartsWith("-");
+            var i = negative || text.StartsWith("+") ? 1 : 0;
+            var start = i;
+            double value = 0;
+            for (; i < text.Length; i++)
+            {
+                var digit = Digits.IndexOf(char.ToLowerInvariant(text[i]));
+                if (digit == -1 || digit >= radix)
+                {
+                    break;
+                }
+
+                value = value * radix + digit;
+            }
+
+            if (i == start)
+            {
+                return null;
+            }
+
+            return (float) (negative ? -value : value);
+        }
+
+        private static bool IsNumber(object value)
+        {
+            return value is float || value is int || value is double;
+        }
+
+        private static object Length(object e, bool chars)
+        {
+            switch (e)
+            {
+                case string s:
+                    return (float) new TextUnits(s, chars).Count;
+                case DmList list:
+                    return (float) list.Count;
+                default:
+                    return 0f;
+            }
+        }
+
+        private static object CopyText(object t, object start, object end, bool chars)
+        {
+            if (!(t is string s))
+            {
+                return null;
+            }
+
+            var units = new TextUnits(s, chars);
+            var from = units.Resolve(start, 1);
+            var to = units.Resolve(end, 0);
+            if (to <= from)
+            {
+                return "";
+            }
+
+            return units.Slice(from, to);
+        }
+
+        private static object FindText(object haystack, object needle, object start, object end,
+            StringComparison comparison, bool chars)
+        {
+            if (DmRegex.IsRegex(needle))
+            {
+                return DmRegex.Find(needle, haystack, start, end);
+            }
+
+            if (!(haystack is string s) || !(needle is string n))
+            {
+                return 0f;
+            }
+
+            var units = new TextUnits(s, chars);
+            var from = units.Index(units.Resolve(start, 1));
+            var to = units.Index(units.Resolve(end, 0));
+            if (to < from)
+            {
+                return 0f;
+            }
+
+            var found = s.IndexOf(n, from, to - from, comparison);
+            return found == -1 ? 0f : (float) units.Position(found);
+        }
+
+        /// <summary>
+        ///     Like <c>findtext()</c>, but searches backwards from <paramref name="start" /> (default: the end)
+        ///     to <paramref name="end" /> (default: the start).
+        /// </summary>
+        private static object FindLastText(object haystack, object needle, object start, object end,
+            StringComparison comparison, bool chars)
+        {
+            if (!(haystack is string s) || !(needle is string n))
+            {
+                return 0f;
+            }
+
+            var units = new TextUnits(s, chars);
+            var from = Math.Min(units.Index(units.Resolve(start, 0)), s.Length - n.Length);
+            var to = units.Index(units.Resolve(end, 1));
+            for (var i = from; i >= to; i--)
+            {
+                if (string.Compare(s, i, n, 0, n.Length, comparison) == 0)
+                {
+                    return (float) units.Position(i);
+                }
+            }
+
+            return 0f;
+        }
+
+        private static object ReplaceText(object haystack, object needle, object replacement, object start,
+            object end, StringComparison comparison, bool chars)
+        {
+            if (DmRegex.IsRegex(needle))
+            {
+                return DmRegex.Replace(needle, haystack, replacement, start, end);
+            }
+
+            if (!(haystack is string s) || !(needle is string n) || n.Length == 0)
+            {
+                return haystack;
+            }
+
+            var units = new TextUnits(s, chars);
+            var from = units.Index(units.Resolve(start, 1));
+            var to = units.Index(units.Resolve(end, 0));
+            var replacementText = Stringify(replacement);
+
+            var builder = new StringBuilder();
+            var pos = from;
+            builder.Append(s, 0, from);
+            while (pos < to)
+            {
+                var found = s.IndexOf(n, pos, to - pos, comparison);
+                if (found == -1)
+                {
+                    break;
+                }
+
+                builder.Append(s, pos, found - pos);
+                builder.Append(replacementText);
+                pos = found + n.Length;
+            }
+
+            builder.Append(s, pos, s.Length - pos);
+            return builder.ToString();
+        }
+
+        /// <summary>
+        ///     Text before <paramref name="start" /> ends up in the first item, text after <paramref name="end" />
+        ///     in the last one.
+        /// </summary>
+        private static object SplitText(object text, object delimiter, object start, object end,
+            object includeDelimiters, bool chars)
+        {
+            var list = new DmList();
+            if (!(text is string s))
+            {
+                return list;
+            }
+
+            var units = new TextUnits(s, chars);
+            var from = units.Index(units.Resolve(start, 1));
+            var to = units.Index(units.Resolve(end, 0));
+            if (DmRegex.IsRegex(delimiter))
+            {
+                return DmRegex.Split(delimiter, s, from, to, DmInternal.Truthy(includeDelimiters));
+            }
+
+            var itemStart = 0;
+            if (delimiter is string d && d.Length != 0)
+            {
+                var pos = from;
+                while (pos < to)
+                {
+                    var found = s.IndexOf(d, pos, to - pos, StringComparison.Ordinal);
+                    if (found == -1)
+                    {
+                        break;
+                    }
+
+                    list.Append(s.Substring(itemStart, found - itemStart));
+                    if (DmInternal.Truthy(includeDelimiters))
+                    {
+                        list.Append(d);
+                    }
+
+                    itemStart = pos = found + d.Length;
+                }
+            }
+
+            list.Append(s.Substring(itemStart));
+            return list;
+        }
+
+        private static object Text2Ascii(object t, object pos, bool chars)
+        {
+            if (!(t is string s))
+            {
+                return 0f;
+            }
+
+            var units = new TextUnits(s, chars);
+            var position = units.Resolve(pos, 1);
+            if (position > units.Count)
+            {
+                return 0f;
+            }
+
+            return (float) CodePointAt(s, units.Index(position));
+        }
+
+        /// <summary>
+        ///     Counts how long the run of text starting at <paramref name="start" /> is,
+        ///     where every character is (or with <paramref name="inNeedles" /> false, isn't) in the needles.
+        /// </summary>
+        private static object SpanText(object haystack, object needles, object start, bool inNeedles, bool chars)
+        {
+            if (!(haystack is string s))
+            {
+                return 0f;
+            }
+
+            var needleText = Stringify(needles);
+            var units = new TextUnits(s, chars);
+            var index = units.Index(units.Resolve(start, 1));
+            var spanStart = index;
+            while (index < s.Length)
+            {
+                var length = char.IsSurrogatePair(s, index) ? 2 : 1;
+                if ((needleText.IndexOf(s.Substring(index, length), StringComparison.Ordinal) != -1) != inNeedles)
+                {
+                    break;
+                }
+
+                index += length;
+            }
+
+            return (float) (units.Position(index) - units.Position(spanStart));
+        }
+
+        private static int CodePointAt(string s, int index)
+        {
+            return char.IsSurrogatePair(s, index) ? char.ConvertToUtf32(s, index) : s[index];
+        }
+
+        /// <summary>
+        ///     Turns a DM position argument into a position between 1 and <c>count + 1</c>.
+        /// </summary>
+        private static int ResolvePosition(object position, int fallback, int count)
+        {
+            var pos = ToInt(position, fallback);
+            if (pos <= 0)
+            {
+                pos += count + 1;
+            }
+
+            return Math.Max(1, Math.Min(pos, count + 1));
+        }
+
+        private static int ToInt(object value, int fallback)
+        {
+            switch (value)
+            {
+                case float f:
+                    return (int) f;
+                case int i:
+                    return i;
+                case double d:
+                    return (int) d;
+                default:
+                    return fallback;
+            }
+        }
+
+        /// <summary>
+        ///     Maps DM positions, counted in UTF-8 bytes or characters, to indices in a .NET string.
+        /// </summary>
+        internal sealed class TextUnits
+        {
+            /// <summary>
+            ///     The string index each unit starts at, followed by the length of the string.
+            ///     The bytes of a multi-byte character all start where the character starts.
+            /// </summary>
+            private readonly int[] _starts;
+
+            private readonly string _text;
+
+            public TextUnits(string text, bool chars)
+            {
+                _text = text;
+                var starts = new List<int>(text.Length + 1);
+                for (var i = 0; i < text.Length; i++)
+                {
+                    var start = i;
+                    int bytes;
+                    if (char.IsSurrogatePair(text, i))
+                    {
+                        bytes = 4;
+                        i++;
+                    }
+                    else
+                    {
+                        bytes = text[i] < 0x80 ? 1 : text[i] < 0x800 ? 2 : 3;
+                    }
+
+                    for (var b = 0; b < (chars ? 1 : bytes); b++)
+                    {
+                        starts.Add(start);
+                    }
+                }
+
+                starts.Add(text.Length);
+                _starts = starts.ToArray();
+            }
+
+            public int Count => _starts.Length - 1;
+
+            public int Resolve(object position, int fallback)
+            {
+                return ResolvePosition(position, fallback, Count);
+            }
+
+            /// <summary>
+            ///     The string index of a resolved position.
+            /// </summary>
+            public int Index(int position)
+            {
+                return _starts[position - 1];
+            }
+
+            /// <summary>
+            ///     The position of a string index.
+            /// </summary>
+            public int Position(int index)
+            {
+                for (var i = 0; i < _starts.Length; i++)
+                {
+                    if (_starts[i] >= index)
+                    {
+                        return i + 1;
+                    }
+                }
+
+                return _starts.Length;
+            }
+
+            public string Slice(int from, int to)
+            {
+                var start = Index(from);
+                return _text.Substring(start, Index(to) - start);
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+using System;
+using System.Globalization;
+using System.Text;
+
+namespace DM
+{
+    /// <summary>
+    ///     Real world time, and <c>time2text()</c>. DM counts time in deciseconds.
+    /// </summary>
+    public static class DmTime
+    {
+        private const double DecisecondsPerDay = 24 * 60 * 60 * 10;
+        private const string DefaultFormat = "DDD MMM DD hh:mm:ss YYYY";
+
+        /// <summary>
+        ///     Where <c>world.realtime</c> counts from.
+        /// </summary>
+        private static readonly DateTime Epoch = new DateTime(2000, 1, 1, 0, 0, 0, DateTimeKind.Utc);
+
+        /// <summary>
+        ///     The tokens <c>time2text()</c> replaces. Longer ones first, so <c>YYYY</c> isn't read as <c>YY</c> twice.
+        /// </summary>
+        private static readonly string[] Tokens =
+            {"YYYY", "Month", "Day", "MMM", "DDD", "YY", "MM", "DD", "hh", "mm", "ss"};
+
+        /// <summary>
+        ///     <c>world.realtime</c>: deciseconds since the start of 2000, UTC.
+        /// </summary>
+        public static double RealTime => (DateTime.UtcNow - Epoch).Ticks / (double) TimeSpan.TicksPerSecond * 10;
+
+        /// <summary>
+        ///     <c>world.timeofday</c>: deciseconds since midnight, local time.
+        /// </summary>
+        public static double TimeOfDay => DateTime.Now.TimeOfDay.Ticks / (double) TimeSpan.TicksPerSecond * 10;
+
+        /// <summary>
+        ///     Implements <c>time2text(timestamp, format, timezone)</c>.
+        ///     The timestamp is like <c>world.realtime</c>, shown in the local time zone unless
+        ///     <paramref name="timezone" /> gives an offset from UTC in hours.
+        ///     Timestamps less than a day long are a time of day, like <c>world.timeofday</c>.
+        /// </summary>
+        public static object Time2Text(object timestamp, object format, object timezone)
+        {
+            var deciseconds = DmMath.ToNumber(timestamp, "timestamp");
+            var offset = TimeSpan.FromSeconds(deciseconds / 10);
+            DateTime time;
+            if (deciseconds >= 0 && deciseconds < DecisecondsPerDay)
+            {
+                time = new DateTime(2000, 1, 1) + offset;
+            }
+            else if (timezone == null)
+            {
+                time = (Epoch + offset).ToLocalTime();
+            }
+            else
+            {
+                time = (Epoch + offset).AddHours(DmMath.ToNumber(timezone, "timezone"));
+            }
+
+            return Format(time, format == null ? DefaultFormat : DmText.Stringify(format));
+        }
+
+        private static string Format(DateTime time, string format)
+        {
+            var culture = CultureInfo.InvariantCulture;
+            var builder = new StringBuilder();
+            var pos = 0;
+            while (pos < format.Length)
+            {
+                var token = MatchToken(format, pos);
+                if (token == null)
+                {
+                    builder.Append(format[pos]);
+                    pos++;
+                    continue;
+                }
+
+                switch (token)
+                {
+                    case "YYYY":
+                        builder.Append(time.Year.ToString("0000", culture));
+                        break;
+                    case "YY":
+                        builder.Append((time.Year % 100).ToString("00", culture));
+                        break;
+                    case "Month":
+                        builder.Append(time.ToString("MMMM", culture));
+                        break;
+                    case "MMM":
+                        builder.Append(time.ToString("MMM", culture));
+                        break;
+                    case "MM":
+                        builder.Append(time.Month.ToString("00", culture));
+                        break;
+                    case "Day":
+                        builder.Append(time.ToString("dddd", culture));
+                        break;
+                    case "DDD":
+                        builder.Append(time.ToString("ddd", culture));
+                        break;
+                    case "DD":
+                        builder.Append(time.Day.ToString("00", culture));
+                        break;
+                    case "hh":
+                        builder.Append(time.Hour.ToString("00", culture));
+                        break;
+                    case "mm":
+                        builder.Append(time.Minute.ToString("00", culture));
+                        break;
+                    case "ss":
+                        builder.Append(time.Second.ToString("00", culture));
+                        break;
+                }
+
+                pos += token.Length;
+            }
+
+            return builder.ToString();
+        }
+
+        private static string MatchToken(string format, int pos)
+        {
+            foreach (var token in Tokens)
+            {
+                if (string.CompareOrdinal(format, pos, token, 0, token.Length) == 0)
+                {
+                    return token;
+                }
+            }
+
+            return null;
+        }
+    }
+}
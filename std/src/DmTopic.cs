@@ -0,0 +1,308 @@
+using System;
+using System.IO;
+using System.Net;
+using System.Net.Sockets;
+using System.Text;
+using System.Threading;
+using System.Threading.Tasks;
+
+namespace DM
+{
+    /// <summary>
+    ///     BYOND's Topic protocol: <c>world.Export("byond://host:port?query")</c> in one world
+    ///     ends up in <c>world/Topic()</c> of another.
+    /// </summary>
+    /// <remarks>
+    ///     Every packet starts with <c>00 83</c> and the length of the rest as a big endian short.
+    ///     A query is five zero bytes and the null terminated text, starting with <c>?</c>.
+    ///     A response is a type byte, then a little endian float or null terminated text.
+    /// </remarks>
+    public static class DmTopic
+    {
+        private const byte TypeNull = 0x00;
+        private const byte TypeFloat = 0x2a;
+        private const byte TypeText = 0x06;
+
+        private static TcpListener _listener;
+
+        /// <summary>
+        ///     Whether the world is listening for Topic calls, which keeps the program running.
+        /// </summary>
+        internal static bool IsListening => _listener != null;
+
+        /// <summary>
+        ///     Starts listening for Topic calls on <paramref name="port" />.
+        ///     Returns false if the port couldn't be opened.
+        /// </summary>
+        internal static bool Listen(int port)
+        {
+            Close();
+            var listener = new TcpListener(IPAddress.Any, port);
+            try
+            {
+                listener.Start();
+            }
+            catch (SocketException)
+            {
+                return false;
+            }
+
+            _listener = listener;
+            var thread = new Thread(() => Accept(listener)) {IsBackground = true};
+            thread.Start();
+            return true;
+        }
+
+        internal static void Close()
+        {
+            _listener?.Stop();
+            _listener = null;
+        }
+
+        /// <summary>
+        ///     Sends a query to another world and reads what its <c>world/Topic()</c> returned.
+        /// </summary>
+        internal static object Call(string host, int port, string query)
+        {
+            using (var client = new TcpClient(host, port))
+            {
+                var stream = client.GetStream();
+                var packet = EncodeQuery(query);
+                stream.Write(packet, 0, packet.Length);
+                return ReadResponse(stream);
+            }
+        }
+
+        /// <summary>
+        ///     Encodes the packet for a query, like <c>?ping</c>.
+        /// </summary>
+        private static byte[] EncodeQuery(string query)
+        {
+            if (!query.StartsWith("?"))
+            {
+                query = "?" + query;
+            }
+
+            var body = new MemoryStream();
+            body.Write(new byte[5], 0, 5);
+            WriteText(body, query);
+            return Packet(body.ToArray());
+        }
+
+        /// <summary>
+        ///     Reads a response packet, which is a number, text or null.
+        /// </summary>
+        private static object ReadResponse(Stream stream)
+        {
+            var body = ReadPacket(stream);
+            if (body == null || body.Length == 0)
+            {
+                return null;
+            }
+
+            switch (body[0])
+            {
+                case TypeFloat when body.Length >= 5:
+                    if (!BitConverter.IsLittleEndian)
+                    {
+                        Array.Reverse(body, 1, 4);
+                    }
+
+                    return BitConverter.ToSingle(body, 1);
+                case TypeText:
+                    return ReadText(body, 1);
+                default:
+                    return null;
+            }
+        }
+
+        private static void Accept(TcpListener listener)
+        {
+            while (true)
+            {
+                TcpClient client;
+                try
+                {
+                    client = listener.AcceptTcpClient();
+                }
+                catch (Exception e) when (e is SocketException || e is ObjectDisposedException)
+                {
+                    // Stopped listening.
+                    return;
+                }
+
+                var thread = new Thread(() => Serve(client)) {IsBackground = true};
+                thread.Start();
+            }
+        }
+
+        /// <summary>
+        ///     Answers the queries sent over a connection, until the other side is done.
+        /// </summary>
+        private static void Serve(TcpClient client)
+        {
+            using (client)
+            {
+                var stream = client.GetStream();
+                var address = ((IPEndPoint) client.Client.RemoteEndPoint).Address.ToString();
+                try
+                {
+                    while (true)
+                    {
+                        var body = ReadPacket(stream);
+                        if (body == null)
+                        {
+                            return;
+                        }
+
+                        // Skip the padding and the ?.
+                        var query = body.Length > 5 ? ReadText(body, 5) : "";
+                        if (query.StartsWith("?"))
+                        {
+                            query = query.Substring(1);
+                        }
+
+                        var response = EncodeResponse(CallTopic(query, address));
+                        stream.Write(response, 0, response.Length);
+                    }
+                }
+                catch (IOException)
+                {
+                    // The other side went away.
+                }
+            }
+        }
+
+        /// <summary>
+        ///     Runs <c>world/Topic(T, Addr, Master, Keys)</c> on the scheduler and waits for what it returns.
+        ///     If the world shuts down before it returns, the answer is null.
+        /// </summary>
+        private static object CallTopic(string query, string address)
+        {
+            var result = new TaskCompletionSource<object>();
+            var runEnded = DmScheduler.Post(() =>
+            {
+                object value = null;
+                try
+                {
+                    var world = DmWorld.World;
+                    if (world != null)
+                    {
+                        value = DmInternal.CallOverride(world, "Topic", new object[] {query, address, 0f, null});
+                    }
+                }
+                finally
+                {
+                    // Runtime errors still get reported, the caller gets null.
+                    result.TrySetResult(value);
+                }
+            });
+
+            try
+            {
+                result.Task.Wait(runEnded);
+            }
+            catch (OperationCanceledException)
+            {
+                return null;
+            }
+
+            return result.Task.Result;
+        }
+
+        private static byte[] EncodeResponse(object value)
+        {
+            var body = new MemoryStream();
+            switch (value)
+            {
+                case float _:
+                case int _:
+                case double _:
+                    var number = BitConverter.GetBytes(Convert.ToSingle(value));
+                    if (!BitConverter.IsLittleEndian)
+                    {
+                        Array.Reverse(number);
+                    }
+
+                    body.WriteByte(TypeFloat);
+                    body.Write(number, 0, number.Length);
+                    break;
+                case string text:
+                    body.WriteByte(TypeText);
+                    WriteText(body, text);
+                    break;
+                default:
+                    body.WriteByte(TypeNull);
+                    break;
+            }
+
+            return Packet(body.ToArray());
+        }
+
+        private static byte[] Packet(byte[] body)
+        {
+            var packet = new byte[body.Length + 4];
+            packet[0] = 0x00;
+            packet[1] = 0x83;
+            packet[2] = (byte) (body.Length >> 8);
+            packet[3] = (byte) body.Length;
+            Array.Copy(body, 0, packet, 4, body.Length);
+            return packet;
+        }
+
+        /// <summary>
+        ///     Reads the body of a packet, or null if the connection closed first.
+        /// </summary>
+        private static byte[] ReadPacket(Stream stream)
+        {
+            var header = ReadExactly(stream, 4);
+            if (header == null)
+            {
+                return null;
+            }
+
+            if (header[0] != 0x00 || header[1] != 0x83)
+            {
+                throw new IOException("Not a Topic packet.");
+            }
+
+            return ReadExactly(stream, (header[2] << 8) | header[3]);
+        }
+
+        private static byte[] ReadExactly(Stream stream, int count)
+        {
+            var buffer = new byte[count];
+            var read = 0;
+            while (read < count)
+            {
+                var got = stream.Read(buffer, read, count - read);
+                if (got == 0)
+                {
+                    return null;
+                }
+
+                read += got;
+            }
+
+            return buffer;
+        }
+
+        private static void WriteText(Stream stream, string text)
+        {
+            var bytes = Encoding.UTF8.GetBytes(text);
+            stream.Write(bytes, 0, bytes.Length);
+            stream.WriteByte(0);
+        }
+
+        private static string ReadText(byte[] body, int start)
+        {
+            var end = Array.IndexOf(body, (byte) 0, start);
+            if (end == -1)
+            {
+                end = body.Length;
+            }
+
+            return Encoding.UTF8.GetString(body, start, end - start);
+        }
+    }
+}
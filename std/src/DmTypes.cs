@@ -0,0 +1,39 @@
+namespace DM
+{
+    /// <summary>
+    ///     Implementations of the type check builtins that can't be done with a plain isinst.
+    /// </summary>
+    public static class DmTypes
+    {
+        /// <summary>
+        ///     Implements <c>istype(Val, Type)</c> when the type isn't known at compile time.
+        /// </summary>
+        public static object IsType(object val, object type)
+        {
+            if (val == null || !(type is DmPath path))
+            {
+                return DmInternal.Bool(false);
+            }
+
+            return DmInternal.Bool(path.ClrType.IsInstanceOfType(val));
+        }
+
+        /// <summary>
+        ///     Implements <c>ispath(Val)</c> and <c>ispath(Val, Type)</c>.
+        /// </summary>
+        public static object IsPath(object val, object type)
+        {
+            if (!(val is DmPath path))
+            {
+                return DmInternal.Bool(false);
+            }
+
+            if (type == null)
+            {
+                return DmInternal.Bool(true);
+            }
+
+            return DmInternal.Bool(path.IsSubtypeOf(type as DmPath));
+        }
+    }
+}
@@ -0,0 +1,111 @@
+using System;
+using System.Reflection;
+
+namespace DM
+{
+    /// <summary>
+    ///     Compiled metadata about a var on a type.
+    /// </summary>
+    public sealed class DmVarInfo
+    {
+        public object Initial { get; }
+        public bool IsSaved { get; }
+
+        public DmVarInfo(object initial, bool isSaved)
+        {
+            Initial = initial;
+            IsSaved = isSaved;
+        }
+    }
+
+    /// <summary>
+    ///     Reflection over the vars of objects: <c>initial()</c>, <c>issaved()</c> and <c>vars</c>.
+    /// </summary>
+    public static class DmVars
+    {
+        /// <summary>
+        ///     Implements <c>initial(Obj.Var)</c>.
+        /// </summary>
+        public static object Initial(object obj, string name)
+        {
+            return DmPath.Of(obj)?.GetVar(name)?.Initial;
+        }
+
+        /// <summary>
+        ///     Implements <c>issaved(Obj.Var)</c>.
+        /// </summary>
+        public static object IsSaved(object obj, string name)
+        {
+            return DmInternal.Bool(DmPath.Of(obj)?.GetVar(name)?.IsSaved ?? false);
+        }
+
+        /// <summary>
+        ///     Implements <c>Obj.vars</c>.
+        /// </summary>
+        public static DmList VarsOf(object obj)
+        {
+            if (obj == null)
+            {
+                throw new NullReferenceException("Cannot read null.vars");
+            }
+
+            return new DmVarsList(obj);
+        }
+
+        public static FieldInfo GetField(object obj, string name)
+        {
+            return obj.GetType().GetField(name, BindingFlags.Public | BindingFlags.Instance);
+        }
+    }
+
+    /// <summary>
+    ///     The <c>vars</c> list of an object. Keys are var names, reading and writing them goes to the object.
+    /// </summary>
+    public sealed class DmVarsList : DmList
+    {
+        private readonly object _owner;
+
+        public DmVarsList(object owner)
+        {
+            _owner = owner;
+            Append("type");
+            Append("parent_type");
+            Append("vars");
+            foreach (var field in owner.GetType().GetFields(BindingFlags.Public | BindingFlags.Instance))
+            {
+                Append(field.Name);
+            }
+        }
+
+        public override object GetAssoc(object key)
+        {
+            switch (key)
+            {
+                case "type":
+                    return DmPath.Of(_owner);
+                case "parent_type":
+                    return DmPath.ParentOf(_owner);
+                case "vars":
+                    return this;
+            }
+
+            if (key is string name)
+            {
+                return DmVars.GetField(_owner, name)?.GetValue(_owner);
+            }
+
+            return null;
+        }
+
+        public override void SetAssoc(object key, object value)
+        {
+            var field = key is string name ? DmVars.GetField(_owner, name) : null;
+            if (field == null)
+            {
+                throw new InvalidOperationException($"Cannot modify {key ?? "null"} in vars.");
+            }
+
+            field.SetValue(_owner, value);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+using System;
+using System.Collections.Generic;
+using System.Globalization;
+using System.IO;
+using System.Net;
+using System.Net.Sockets;
+using System.Reflection;
+
+namespace DM
+{
+    /// <summary>
+    ///     Keeps the vars of <c>world</c> that the runtime owns up to date.
+    /// </summary>
+    public static class DmWorld
+    {
+        private const float DefaultFps = 10;
+
+        private static object _world;
+
+        /// <summary>
+        ///     The world object, null until the generated code has set it up.
+        /// </summary>
+        internal static object World => _world;
+
+        /// <summary>
+        ///     The tick rate last applied, to notice when DM code changes <c>world.tick_lag</c> or <c>world.fps</c>.
+        /// </summary>
+        private static float _tickLag;
+
+        private static float _fps;
+
+        /// <summary>
+        ///     Whether the program was run with <c>-trusted</c>, which allows <c>shell()</c>.
+        /// </summary>
+        internal static bool Trusted { get; private set; }
+
+        /// <summary>
+        ///     Called by the generated code with the world object, once its vars are initialized.
+        /// </summary>
+        public static void Init(object world)
+        {
+            _world = world;
+            if (Get("log") == null)
+            {
+                Set("log", Console.Error);
+            }
+
+            if (Get("name") == null)
+            {
+                Set("name", Path.GetFileNameWithoutExtension(Assembly.GetEntryAssembly()?.Location ?? "world"));
+            }
+
+            // world/fps = 20 in the code takes precedence over the default tick_lag.
+            var fps = ToNumber(Get("fps"), DefaultFps);
+            var tickLag = ToNumber(Get("tick_lag"), 10 / DefaultFps);
+            SetTickRate(fps != DefaultFps ? 10 / fps : tickLag);
+            UpdateTime();
+        }
+
+        /// <summary>
+        ///     Runs the program the way DM does: <c>world/New()</c>, then the scheduler until the world is shut down
+        ///     or nothing is left to run, then <c>world/Del()</c>.
+        ///     Called by the generated entry point with its command line, unless the program was compiled
+        ///     to start at <c>main()</c>.
+        /// </summary>
+        public static void Run(object world, string[] arguments)
+        {
+            ReadArguments(arguments);
+            DmScheduler.Run(args => DmInternal.CallOverride(world, "New", args));
+            DmTopic.Close();
+            DmScheduler.Run(args => DmInternal.CallOverride(world, "Del", args));
+        }
+
+        /// <summary>
+        ///     Runs a program compiled to start at <c>main()</c>, until nothing is left to run.
+        ///     Called by the generated entry point with its command line.
+        /// </summary>
+        public static void RunMain(Func<object[], object> main, string[] arguments)
+        {
+            ReadArguments(arguments);
+            DmScheduler.Run(main);
+            DmTopic.Close();
+        }
+
+        /// <summary>
+        ///     Implements <c>world.Reboot()</c>. There is no server to restart into, so this ends the program.
+        /// </summary>
+        public static object Reboot(object src, object reason)
+        {
+            DmScheduler.Stop();
+            return null;
+        }
+
+        /// <summary>
+        ///     Implements <c>del(world)</c>.
+        /// </summary>
+        internal static void Delete()
+        {
+            DmScheduler.Stop();
+        }
+
+        /// <summary>
+        ///     Applies changes to <c>world.tick_lag</c> or <c>world.fps</c> made during the tick.
+        /// </summary>
+        internal static void UpdateTickRate()
+        {
+            if (_world == null)
+            {
+                return;
+            }
+
+            var fps = ToNumber(Get("fps"), _fps);
+            var tickLag = ToNumber(Get("tick_lag"), _tickLag);
+            if (fps != _fps && fps > 0)
+            {
+                SetTickRate(10 / fps);
+            }
+            else if (tickLag != _tickLag && tickLag > 0)
+            {
+                SetTickRate(tickLag);
+            }
+            else
+            {
+                // Put back nonsense like world.fps = 0.
+                SetTickRate(_tickLag);
+            }
+        }
+
+        /// <summary>
+        ///     Sets <c>world.time</c>, <c>world.timeofday</c> and <c>world.realtime</c> for a new tick.
+        /// </summary>
+        internal static void UpdateTime()
+        {
+            if (_world == null)
+            {
+                return;
+            }
+
+            Set("time", (float) DmScheduler.Time);
+            Set("timeofday", (float) DmTime.TimeOfDay);
+            Set("realtime", (float) DmTime.RealTime);
+        }
+
+        /// <summary>
+        ///     Writes a line to <c>world.log</c>, which can be a file or the standard error it starts out as.
+        /// </summary>
+        internal static void Log(string text)
+        {
+            switch (_world == null ? null : Get("log"))
+            {
+                case DmFile file:
+                    file.WriteLine(text);
+                    break;
+                case TextWriter writer:
+                    writer.WriteLine(text);
+                    break;
+                default:
+                    Console.Error.WriteLine(text);
+                    break;
+            }
+        }
+
+        /// <summary>
+        ///     Reads the command line like DreamDaemon does: a bare number or <c>-port N</c> is the port,
+        ///     <c>-trusted</c> allows <c>shell()</c>, and <c>-params text</c>, <c>key=value&amp;...</c> text
+        ///     and <c>--key value</c> pairs all end up in <c>world.params</c>. A <c>port</c> param works too.
+        ///     If there is a port, the world listens on it for Topic calls.
+        /// </summary>
+        private static void ReadArguments(string[] arguments)
+        {
+            // world/port = 5000 in the code is the default.
+            var port = Get("port") is float configured ? configured : 0;
+            var parameters = new List<string>();
+            for (var i = 0; i < arguments.Length; i++)
+            {
+                var arg = arguments[i];
+                var hasNext = i + 1 < arguments.Length;
+                if (arg == "-port" && hasNext)
+                {
+                    float.TryParse(arguments[++i], NumberStyles.None, CultureInfo.InvariantCulture, out port);
+                }
+                else if (arg == "-trusted")
+                {
+                    Trusted = true;
+                }
+                else if (arg == "-params" && hasNext)
+                {
+                    parameters.Add(arguments[++i]);
+                }
+                else if (arg.StartsWith("--") && arg.Length > 2)
+                {
+                    var key = DmEncoding.EscapeParam(arg.Substring(2));
+                    if (hasNext && !arguments[i + 1].StartsWith("--"))
+                    {
+                        key += "=" + DmEncoding.EscapeParam(arguments[++i]);
+                    }
+
+                    parameters.Add(key);
+                }
+                else if (float.TryParse(arg, NumberStyles.None, CultureInfo.InvariantCulture, out var number))
+                {
+                    port = number;
+                }
+                else
+                {
+                    parameters.Add(arg);
+                }
+            }
+
+            var paramsList = (DmList) DmEncoding.Params2List(string.Join("&", parameters));
+            if (port == 0 && paramsList.GetAssoc("port") is string portParam)
+            {
+                float.TryParse(portParam, NumberStyles.None, CultureInfo.InvariantCulture, out port);
+            }
+
+            // Like DreamDaemon, world.port is 0 if the port can't be opened.
+            if (port != 0 && !DmTopic.Listen((int) port))
+            {
+                Log($"Unable to open port {port}.");
+                port = 0;
+            }
+
+            Set("params", paramsList);
+            Set("port", port);
+            // Only means anything when the world is hosted.
+            Set("address", port != 0 ? LocalAddress() : null);
+        }
+
+        /// <summary>
+        ///     The IPv4 address of this machine other people would connect to, as far as we can tell.
+        /// </summary>
+        private static string LocalAddress()
+        {
+            try
+            {
+                foreach (var address in Dns.GetHostAddresses(Dns.GetHostName()))
+                {
+                    if (address.AddressFamily == AddressFamily.InterNetwork && !IPAddress.IsLoopback(address))
+                    {
+                        return address.ToString();
+                    }
+                }
+            }
+            catch (SocketException)
+            {
+                // No name for this machine, fall back to loopback.
+            }
+
+            return IPAddress.Loopback.ToString();
+        }
+
+        private static void SetTickRate(float tickLag)
+        {
+            _tickLag = tickLag;
+            _fps = 10 / tickLag;
+            DmScheduler.TickLag = tickLag;
+            Set("tick_lag", _tickLag);
+            Set("fps", _fps);
+        }
+
+        private static float ToNumber(object value, float fallback)
+        {
+            return value == null ? fallback : (float) DmMath.ToNumber(value, "world tick rate");
+        }
+
+        private static object Get(string name)
+        {
+            return DmVars.GetField(_world, name)?.GetValue(_world);
+        }
+
+        private static void Set(string name, object value)
+        {
+            DmVars.GetField(_world, name)?.SetValue(_world, value);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+#!/usr/bin/env python3
+
+# Compiles every tests/*.dm program, runs it and compares what it prints with tests/*.out.
+# Build the DM runtime first (dotnet build std/DM.csproj), and have ilasm on the PATH.
+# Tests asking for input read it from tests/*.in, and tests/*.args holds the command line to run a test with.
+#
+# Usage: util/run_tests.py [test name...] [--dm path/to/DM.dll]
+
+import os
+import shlex
+import shutil
+import subprocess
+import sys
+import tempfile
+
+ROOT = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+TESTS = os.path.join(ROOT, "tests")
+DEFAULT_DM = os.path.join(ROOT, "std", "bin", "Debug", "netstandard2.0", "DM.dll")
+
+def main():
+    args = sys.argv[1:]
+    dm_path = DEFAULT_DM
+    if "--dm" in args:
+        index = args.index("--dm")
+        dm_path = args[index + 1]
+        del args[index:index + 2]
+
+    if not os.path.exists(dm_path):
+        print("DM.dll not found at {}, build std/DM.csproj first.".format(dm_path))
+        sys.exit(1)
+
+    names = args or sorted(name[:-3] for name in os.listdir(TESTS) if name.endswith(".dm"))
+
+    subprocess.check_call(["cargo", "build", "--manifest-path", os.path.join(ROOT, "compiler", "Cargo.toml")])
+    compiler = os.path.join(ROOT, "compiler", "target", "debug", "compiler")
+
+    failed = []
+    for name in names:
+        if not run_test(name, compiler, dm_path):
+            failed.append(name)
+
+    print("{} passed, {} failed.".format(len(names) - len(failed), len(failed)))
+    for name in failed:
+        print("FAILED: {}".format(name))
+
+    sys.exit(1 if failed else 0)
+
+def run_test(name, compiler, dm_path):
+    source = os.path.join(TESTS, name + ".dm")
+    with open(os.path.join(TESTS, name + ".out"), "r") as f:
+        expected = f.read()
+
+    # Every test runs in its own directory, so the ones writing files don't see each other's.
+    with tempfile.TemporaryDirectory() as work:
+        exe = os.path.join(work, name + ".exe")
+        shutil.copy(dm_path, work)
+
+        command = [compiler, source, "-o", exe]
+        if os.name != "nt":
+            # No peverify outside of Windows.
+            command.append("--nopeverify")
+        compiled = subprocess.run(command, stdout=subprocess.PIPE, stderr=subprocess.STDOUT)
+        if compiled.returncode != 0:
+            print("{}: compile error\n{}".format(name, compiled.stdout.decode()))
+            return False
+
+        stdin = b""
+        input_path = os.path.join(TESTS, name + ".in")
+        if os.path.exists(input_path):
+            with open(input_path, "rb") as f:
+                stdin = f.read()
+
+        run = [exe] if os.name == "nt" else ["mono", exe]
+        args_path = os.path.join(TESTS, name + ".args")
+        if os.path.exists(args_path):
+            with open(args_path, "r") as f:
+                run += shlex.split(f.read())
+        result = subprocess.run(run, cwd=work, input=stdin, stdout=subprocess.PIPE, stderr=subprocess.PIPE)
+        actual = result.stdout.decode().replace("\r\n", "\n")
+
+    if actual != expected:
+        print("{}: output differs".format(name))
+        print("--- expected\n{}--- actual\n{}--- stderr\n{}".format(expected, actual, result.stderr.decode()))
+        return False
+
+    print("{}: ok".format(name))
+    return True
+
+if __name__ == '__main__':
+    main()